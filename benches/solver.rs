@@ -0,0 +1,165 @@
+#![warn(rust_2018_idioms)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::cargo)]
+#![warn(clippy::nursery)]
+
+//! Benchmarks of the parser, the rule checks, the rules and the solver.
+
+use std::{convert::TryFrom, fs, path::Path};
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use takuzu::{Cell, Grid, Rule, SolveOptions};
+
+/// The example grids the solver is benchmarked on.
+const GRIDS: [&str; 4] = ["grid1", "grid2", "grid3", "grid4"];
+
+/// Sizes of the generated grids.
+const SIZES: [usize; 8] = [6, 8, 10, 12, 14, 16, 18, 20];
+
+/// Maximum number of nodes searched to check that a generated grid has
+/// a unique solution.
+const MAX_NODES: usize = 100;
+
+fn read_grid(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("grids").join(name);
+    fs::read_to_string(path).expect("failed to read grid")
+}
+
+/// A xorshift pseudo-random number generator, for reproducible benchmarks.
+struct Random(u64);
+
+impl Random {
+    /// Returns a number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        usize::try_from(self.0 % n as u64).unwrap()
+    }
+
+    fn cell(&mut self) -> Cell {
+        if self.below(2) == 0 {
+            Cell::Zero
+        } else {
+            Cell::One
+        }
+    }
+
+    /// Shuffles the coordinates of a grid.
+    fn coords(&mut self, grid: &Grid) -> Vec<(usize, usize)> {
+        let mut coords: Vec<_> =
+            (0..grid.rows()).flat_map(|i| (0..grid.cols()).map(move |j| (i, j))).collect();
+        for k in (1..coords.len()).rev() {
+            coords.swap(k, self.below(k + 1));
+        }
+        coords
+    }
+}
+
+/// Returns a random filled legal grid of a given size: the solution
+/// of a handful of random givens.
+fn random_solution(size: usize, random: &mut Random) -> Grid {
+    let options = SolveOptions::new().with_max_solutions(1).with_max_nodes(10_000);
+    loop {
+        let mut grid = Grid::new(size).unwrap();
+        for coord in random.coords(&grid).into_iter().take(size) {
+            grid[coord] = random.cell();
+            if !grid.is_cell_legal(coord) {
+                grid[coord] = Cell::Empty;
+            }
+        }
+        if let Some(solution) = grid.solve_with(&options).ok().and_then(|mut o| o.solutions.pop()) {
+            return solution;
+        }
+    }
+}
+
+/// Returns a random puzzle of a given size with a unique solution,
+/// emptying the cells of a random solution while it stays unique.
+///
+/// Cells are only emptied if the solver proves the solution unique within
+/// `MAX_NODES` nodes, which keeps the generation of large grids short.
+fn random_puzzle(size: usize, random: &mut Random) -> Grid {
+    let options = SolveOptions::new().with_max_solutions(2).with_max_nodes(MAX_NODES);
+    let mut grid = random_solution(size, random);
+    for coord in random.coords(&grid) {
+        let cell = grid[coord];
+        grid[coord] = Cell::Empty;
+        let outcome = grid.solve_with(&options);
+        if !outcome.is_ok_and(|outcome| outcome.is_complete() && outcome.solutions.len() == 1) {
+            grid[coord] = cell;
+        }
+    }
+    grid
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for name in GRIDS {
+        let s = read_grid(name);
+        group.bench_function(name, |b| b.iter(|| black_box(&s).parse::<Grid>().unwrap()));
+    }
+    group.finish();
+}
+
+fn bench_is_legal(c: &mut Criterion) {
+    let mut group = c.benchmark_group("is_legal");
+    for name in GRIDS {
+        let grid = read_grid(name).parse::<Grid>().unwrap();
+        let solution = grid.solve().unwrap().remove(0);
+        group.bench_function(name, |b| b.iter(|| black_box(&grid).is_legal()));
+        group.bench_function(format!("{name}_solved"), |b| {
+            b.iter(|| black_box(&solution).is_legal());
+        });
+    }
+    group.finish();
+}
+
+fn bench_apply_rule(c: &mut Criterion) {
+    let mut group = c.benchmark_group("apply_rule");
+    for name in GRIDS {
+        let grid = read_grid(name).parse::<Grid>().unwrap();
+        for rule in Rule::ALL {
+            let id = format!("{}/{}", rule.to_string().replace(' ', ""), name);
+            group.bench_function(id, |b| {
+                b.iter_batched_ref(
+                    || grid.clone(),
+                    |grid| grid.apply_rule(rule),
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_solve(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solve");
+    for name in GRIDS {
+        let grid = read_grid(name).parse::<Grid>().unwrap();
+        group.bench_function(name, |b| b.iter(|| black_box(&grid).solve().unwrap()));
+    }
+    group.finish();
+}
+
+fn bench_solve_generated(c: &mut Criterion) {
+    let mut random = Random(0x9e37_79b9_7f4a_7c15);
+    let mut group = c.benchmark_group("solve_generated");
+    group.sample_size(20);
+    for size in SIZES {
+        let grid = random_puzzle(size, &mut random);
+        let id = format!("{size}x{size}");
+        group.bench_function(id, |b| b.iter(|| black_box(&grid).solve().unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_parse,
+    bench_is_legal,
+    bench_apply_rule,
+    bench_solve,
+    bench_solve_generated
+);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+#![no_main]
+
+//! Loads play sessions from arbitrary input, valid saved games or not,
+//! under a node limit.
+
+use std::str;
+
+use libfuzzer_sys::fuzz_target;
+use takuzu::{PlaySession, SolveOptions};
+
+/// Maximum number of nodes searched, to keep each run short.
+const MAX_NODES: usize = 1_000;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = str::from_utf8(data) {
+        let _ = PlaySession::load_with(s, &SolveOptions::new().with_max_nodes(MAX_NODES));
+    }
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+//! Parses arbitrary input into grids, following the rules of takuzu
+//! and the ones selected by the input.
+
+use libfuzzer_sys::fuzz_target;
+use takuzu::Grid;
+use takuzu_fuzz::input;
+
+fuzz_target!(|data: &[u8]| {
+    if let Some((rules, s)) = input(data) {
+        let _ = s.parse::<Grid>();
+        let _ = Grid::parse_with_rules(s, rules);
+    }
+});
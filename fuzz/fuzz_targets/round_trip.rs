@@ -0,0 +1,17 @@
+#![no_main]
+
+//! Checks that the grids parsed from arbitrary input are parsed back
+//! from their display, alone and as lists of solutions.
+
+use libfuzzer_sys::fuzz_target;
+use takuzu::Grid;
+use takuzu_fuzz::input;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((rules, s)) = input(data) else { return };
+    let Ok(grid) = Grid::parse_with_rules(s, rules) else { return };
+    let display = grid.to_string();
+    assert_eq!(Grid::parse_with_rules(&display, rules).as_ref(), Ok(&grid), "\n{}", display);
+    let solutions = format!("{}\n{}", display, display);
+    assert_eq!(grid.parse_solutions(&solutions), Ok(vec![grid.clone(), grid]), "\n{}", solutions);
+});
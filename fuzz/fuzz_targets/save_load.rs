@@ -0,0 +1,41 @@
+#![no_main]
+
+//! Checks that the play sessions loaded from arbitrary input are loaded
+//! back from their save with the same puzzle, moves, marks and notes.
+//! The puzzles are solved under a node limit.
+
+use std::str;
+
+use libfuzzer_sys::fuzz_target;
+use takuzu::{PlaySession, SolveOptions};
+
+/// Maximum number of nodes searched, to keep each run short.
+const MAX_NODES: usize = 1_000;
+
+/// Returns a save without its elapsed time, which goes on after loading.
+fn without_elapsed(save: &str) -> String {
+    save.lines().filter(|line| !line.starts_with("elapsed ")).collect::<Vec<_>>().join("\n")
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = str::from_utf8(data) else { return };
+    let options = SolveOptions::new().with_max_nodes(MAX_NODES);
+    let Ok(mut session) = PlaySession::load_with(s, &options) else { return };
+    session.pause();
+    let save = session.save();
+    let mut loaded = match PlaySession::load_with(&save, &options) {
+        Ok(loaded) => loaded,
+        Err(err) => panic!("save not loaded: {}\n{}", err, save),
+    };
+    loaded.pause();
+    assert_eq!(without_elapsed(&loaded.save()), without_elapsed(&save), "\n{}", save);
+    assert_eq!(loaded.puzzle(), session.puzzle());
+    assert_eq!(loaded.grid(), session.grid());
+    assert_eq!(loaded.log(), session.log());
+    assert!(loaded.elapsed() >= session.elapsed());
+    let (rows, cols) = session.puzzle().dimensions();
+    for coord in (0..rows).flat_map(|i| (0..cols).map(move |j| (i, j))) {
+        assert_eq!(loaded.marks(coord), session.marks(coord));
+        assert_eq!(loaded.note(coord), session.note(coord));
+    }
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+//! Solves the grids parsed from arbitrary input under a node limit,
+//! and checks that the solutions are legal and keep the givens.
+
+use libfuzzer_sys::fuzz_target;
+use takuzu::{Grid, SolveOptions};
+use takuzu_fuzz::input;
+
+/// Maximum number of nodes searched, to keep each run short.
+const MAX_NODES: usize = 1_000;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((rules, s)) = input(data) else { return };
+    let Ok(grid) = Grid::parse_with_rules(s, rules) else { return };
+    let Ok(outcome) = grid.solve_with(&SolveOptions::new().with_max_nodes(MAX_NODES)) else {
+        assert!(!grid.is_legal(), "legal grid rejected:\n{}", grid);
+        return;
+    };
+    for solution in &outcome.solutions {
+        assert!(solution.is_filled() && solution.is_legal(), "illegal solution:\n{}", solution);
+        let givens = grid.as_slice().iter().zip(solution.as_slice());
+        assert!(givens.filter(|(given, _)| given.is_filled()).all(|(a, b)| a == b));
+    }
+});
@@ -0,0 +1,36 @@
+#![warn(rust_2018_idioms)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+
+//! Decoding of the inputs shared by the fuzz targets.
+//!
+//! An input is a byte selecting the rules, followed by a grid in UTF-8.
+//! A null byte selects the rules of takuzu, so the seed corpus is made of
+//! the example grids prefixed with a null byte.
+
+use std::str;
+
+use takuzu::{Balance, RuleSet};
+
+/// Returns the rules selected by the first byte of an input.
+///
+/// The two lowest bits select the maximum run length, the next two the
+/// balance of the lines, and the fifth bit disables unique lines.
+#[must_use]
+pub fn rules(byte: u8) -> RuleSet {
+    let max_run = [Some(2), Some(1), Some(3), None][usize::from(byte & 0b11)];
+    let balance = [Balance::Equal, Balance::Relaxed, Balance::Free, Balance::Equal]
+        [usize::from(byte >> 2 & 0b11)];
+    RuleSet::TAKUZU
+        .with_max_run(max_run)
+        .with_balance(balance)
+        .with_unique_lines(byte & 0b1_0000 == 0)
+}
+
+/// Splits an input into the rules and the grid to parse, or returns `None`
+/// if the input is empty or the grid is not valid UTF-8.
+#[must_use]
+pub fn input(data: &[u8]) -> Option<(RuleSet, &str)> {
+    let (&byte, grid) = data.split_first()?;
+    Some((rules(byte), str::from_utf8(grid).ok()?))
+}
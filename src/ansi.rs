@@ -1,59 +1,146 @@
-macro_rules! ansi_esc {
-    () => {
-        '\u{1b}'
-    };
-}
+use std::fmt::{self, Display};
 
-macro_rules! ansi_color {
-    ($color_number:expr) => {
-        concat!(ansi_esc!(), '[', $color_number, 'm')
-    };
+use crate::{Cell, Grid, PrettyGrid, Rule};
+
+/// The escape sequence resetting all attributes.
+const RESET: &str = "\u{1b}[0m";
+
+/// A foreground color for ANSI terminals.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Color {
+    /// One of the 16 standard colors.
+    /// Values 0 to 7 are the normal colors, 8 to 15 their bright variants.
+    /// Other values are invalid.
+    Standard(u8),
+    /// One of the colors of the 256-color palette.
+    Fixed(u8),
+    /// A 24-bit color, for terminals supporting truecolor.
+    Rgb(u8, u8, u8),
 }
 
-macro_rules! ansi_color_reset {
-    () => {
-        ansi_color!(0)
-    };
+impl Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Standard(n @ 0..=7) => write!(f, "\u{1b}[3{n}m"),
+            Self::Standard(n) => {
+                debug_assert!(n < 16, "invalid standard color {}", n);
+                write!(f, "\u{1b}[9{}m", n % 8)
+            }
+            Self::Fixed(n) => write!(f, "\u{1b}[38;5;{n}m"),
+            Self::Rgb(r, g, b) => write!(f, "\u{1b}[38;2;{r};{g};{b}m"),
+        }
+    }
 }
 
-macro_rules! mk_color {
-    ($color_number:expr, $str:expr) => {
-        concat!(ansi_color!($color_number), $str, ansi_color_reset!())
-    };
+/// A set of colors used to display grids in ANSI terminals.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Theme {
+    /// Color of the `0`s filled in by the solver.
+    pub zero: Color,
+    /// Color of the `1`s filled in by the solver.
+    pub one: Color,
+    /// Color of the cells that overwrote the reference grid.
+    pub error: Color,
+    /// Colors of the cells filled by each rule, in the order of [`Rule::ALL`],
+    /// when drawing the search of the solver.
+    pub rules: [Color; Rule::ALL.len()],
+    /// Color of the cells guessed, when drawing the search of the solver.
+    pub guess: Color,
 }
 
-macro_rules! red {
-    ($str:expr) => {
-        mk_color!(31, $str)
-    };
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
 }
 
-macro_rules! yellow {
-    ($str:expr) => {
-        mk_color!(33, $str)
+impl Theme {
+    /// Cyan, yellow and red, from the standard terminal colors.
+    pub const DEFAULT: Self = Self {
+        zero: Color::Standard(6),
+        one: Color::Standard(3),
+        error: Color::Standard(1),
+        rules: [
+            Color::Standard(6),
+            Color::Standard(4),
+            Color::Standard(2),
+            Color::Standard(5),
+            Color::Standard(3),
+        ],
+        guess: Color::Standard(15),
     };
-}
-macro_rules! cyan {
-    ($str:expr) => {
-        mk_color!(36, $str)
+    /// Blue, orange and magenta, distinguishable with the common forms of
+    /// color blindness. Requires 256-color support.
+    pub const COLORBLIND: Self = Self {
+        zero: Color::Fixed(32),
+        one: Color::Fixed(214),
+        error: Color::Fixed(199),
+        rules: [
+            Color::Fixed(74),
+            Color::Fixed(32),
+            Color::Fixed(36),
+            Color::Fixed(214),
+            Color::Fixed(227),
+        ],
+        guess: Color::Fixed(255),
     };
-}
-
-use std::fmt::{self, Display};
+    /// The default colors, from the 256-color palette.
+    pub const ANSI256: Self = Self {
+        zero: Color::Fixed(44),
+        one: Color::Fixed(220),
+        error: Color::Fixed(196),
+        rules: [
+            Color::Fixed(44),
+            Color::Fixed(33),
+            Color::Fixed(40),
+            Color::Fixed(170),
+            Color::Fixed(220),
+        ],
+        guess: Color::Fixed(231),
+    };
+    /// The default colors, in 24-bit colors.
+    pub const TRUECOLOR: Self = Self {
+        zero: Color::Rgb(0x2a, 0xa1, 0x98),
+        one: Color::Rgb(0xe5, 0xb5, 0x00),
+        error: Color::Rgb(0xdc, 0x32, 0x2f),
+        rules: [
+            Color::Rgb(0x2a, 0xa1, 0x98),
+            Color::Rgb(0x26, 0x8b, 0xd2),
+            Color::Rgb(0x85, 0x99, 0x00),
+            Color::Rgb(0xd3, 0x36, 0x82),
+            Color::Rgb(0xe5, 0xb5, 0x00),
+        ],
+        guess: Color::Rgb(0xfd, 0xf6, 0xe3),
+    };
+    /// Names of the predefined themes, as accepted by [`Theme::named`].
+    pub const NAMES: [&'static str; 4] = ["default", "colorblind", "256", "truecolor"];
 
-use crate::{Cell, Grid};
+    /// Returns the predefined theme with the given name,
+    /// or `None` if there is no such theme.
+    #[must_use]
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::DEFAULT),
+            "colorblind" => Some(Self::COLORBLIND),
+            "256" => Some(Self::ANSI256),
+            "truecolor" => Some(Self::TRUECOLOR),
+            _ => None,
+        }
+    }
+}
 
 /// Displays a colored diff in ANSI terminals.
 ///
 /// The first grid is used as a reference and the second grid will be displayed.
 /// Cells in the second grid that differ from the reference will be displayed
-/// in color.
+/// in color, using the default [`Theme`] unless another one is selected with
+/// [`with_theme`](AnsiGridDiff::with_theme).
 ///
-/// If the grids have different sizes, the second grid will be displayed normally.
+/// If the grids have different dimensions, the second grid will be displayed normally.
 ///
 /// # Warning
 ///
-/// A red-colored cell signals that a `0` or a `1` from the reference grid
+/// A cell in the error color (red by default) signals that a `0` or a `1` from the reference grid
 /// was overwritten. If `reference` is the original grid and `self`
 /// is a solution, this should *never* happen.
 #[derive(Copy, Clone, Debug)]
@@ -62,43 +149,98 @@ pub struct AnsiGridDiff<'a>(pub &'a Grid, pub &'a Grid);
 
 impl Display for AnsiGridDiff<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let ref_size = self.0.size();
-        if ref_size != self.1.size() {
-            return write!(f, "{}", self.1);
+        self.with_theme(&Theme::DEFAULT).fmt(f)
+    }
+}
+
+impl<'a> AnsiGridDiff<'a> {
+    /// Displays the diff with the colors of `theme`.
+    #[must_use]
+    pub const fn with_theme(self, theme: &'a Theme) -> ThemedGridDiff<'a> {
+        ThemedGridDiff { diff: self, theme }
+    }
+
+    /// Displays the diff with box-drawing characters.
+    #[must_use]
+    pub const fn pretty(self) -> PrettyGrid<'a> {
+        self.with_theme(&Theme::DEFAULT).pretty()
+    }
+}
+
+/// An [`AnsiGridDiff`] displayed with the colors of a [`Theme`].
+///
+/// Created by [`AnsiGridDiff::with_theme`].
+#[derive(Copy, Clone, Debug)]
+pub struct ThemedGridDiff<'a> {
+    diff: AnsiGridDiff<'a>,
+    theme: &'a Theme,
+}
+
+impl<'a> ThemedGridDiff<'a> {
+    /// Displays the diff with box-drawing characters.
+    #[must_use]
+    pub const fn pretty(self) -> PrettyGrid<'a> {
+        let AnsiGridDiff(reference, grid) = self.diff;
+        grid.pretty().diff(reference, self.theme)
+    }
+}
+
+impl Display for ThemedGridDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let AnsiGridDiff(reference, grid) = self.diff;
+        if reference.dimensions() != grid.dimensions() {
+            return write!(f, "{grid}");
         }
-        let ref_rows = self.0.as_slice().chunks(ref_size);
-        let rows = self.1.as_slice().chunks(ref_size);
-        for (ref_row, row) in ref_rows.zip(rows) {
-            for (ref_cell, cell) in ref_row.iter().zip(row) {
-                #[rustfmt::skip]
-                let s = match cell {
-                    Cell::Zero => {
-                        // No color if nothing changed.
-                        if ref_cell == cell { "0" }
-                        // Color for 0 if we filled in a blank.
-                        else if ref_cell.is_empty() { cyan!('0') }
-                        // Red for error if we overwrote.
-                        else { red!('0') }
-                    },
-                    Cell::One => {
-                        // No color if nothing changed.
-                        if ref_cell == cell { "1" }
-                        // Color for 1 if we filled in a blank.
-                        else if ref_cell.is_empty() { yellow!('1') }
-                        // Red for error if we overwrote.
-                        else { red!('1') }
-                    },
-                    Cell::Empty => {
-                        // No color if nothing changed.
-                        if ref_cell == cell { "." }
-                        // Red for error if we overwrote.
-                        else { red!('.') }
-                    }
-                };
-                f.write_str(s)?;
+        grid.fmt_cells(f, |f, coord| write_cell_diff(f, self.theme, reference[coord], grid[coord]))
+    }
+}
+
+/// Displays a grid in ANSI terminals with a color for each cell.
+///
+/// The colors are given row by row. Cells without a color, or beyond the end
+/// of the colors, are displayed normally.
+///
+/// ```
+/// use takuzu::{Color, Grid, PaintedGrid};
+///
+/// let grid = "01\n10\n".parse::<Grid>().unwrap();
+/// let colors = [Some(Color::Standard(2)), None];
+/// assert_eq!(PaintedGrid(&grid, &colors).to_string(), "\u{1b}[32m0\u{1b}[0m1\n10\n");
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct PaintedGrid<'a>(pub &'a Grid, pub &'a [Option<Color>]);
+
+impl Display for PaintedGrid<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let PaintedGrid(grid, colors) = *self;
+        let cols = grid.dimensions().1;
+        grid.fmt_cells(f, |f, (i, j)| {
+            let c = grid[(i, j)].to_char();
+            match colors.get(i * cols + j).copied().flatten() {
+                Some(color) => write!(f, "{color}{c}{RESET}"),
+                None => write!(f, "{c}"),
             }
-            writeln!(f)?;
-        }
-        Ok(())
+        })
     }
 }
+
+/// Writes `cell`, colored according to how it differs from `ref_cell`.
+pub fn write_cell_diff(
+    f: &mut fmt::Formatter<'_>,
+    theme: &Theme,
+    ref_cell: Cell,
+    cell: Cell,
+) -> fmt::Result {
+    let c = cell.to_char();
+    let color = match cell {
+        // No color if nothing changed.
+        _ if ref_cell == cell => return write!(f, "{c}"),
+        // Red for error if we overwrote.
+        _ if ref_cell.is_filled() => theme.error,
+        // Color for 0 or 1 if we filled in a blank.
+        Cell::Zero => theme.zero,
+        Cell::One => theme.one,
+        Cell::Empty => unreachable!("an empty cell can only differ from a filled one"),
+    };
+    write!(f, "{color}{c}{RESET}")
+}
@@ -5,44 +5,53 @@ use std::{
 };
 
 use cell::Cell;
-use error::{GridError, GridParseError, GridSizeError};
+use edge::{Edge, Edges};
+use error::{GridParseError, GridSizeError};
+use line::Line;
+use rules::RuleSet;
+use solve::Rule;
 use Cell::{Empty, One, Zero};
 
 pub mod cell;
+pub mod edge;
 pub mod error;
+pub mod line;
+mod lookahead;
+pub mod rules;
+pub mod solve;
+mod symmetry;
+pub mod tracker;
+pub mod verify;
 
 /// An opaque container for manipulating takuzu grids.
 ///
 /// It provides the internal logic and other convenience functions.
 /// To create a `Grid` you can:
 ///
-/// * create an empty one yourself with [`Grid::new`].
+/// * create an empty one yourself with [`Grid::new`]
+///   or [`Grid::with_dimensions`].
 /// * use the [`FromStr`](#impl-FromStr) trait, e.g. by calling [`parse`](str::parse) on a string.
 ///
+/// A grid follows a [`RuleSet`], the classic takuzu rules unless specified
+/// otherwise with [`Grid::with_rules`] or [`Grid::parse_with_rules`].
+/// Grids parsed with [`FromStr`](#impl-FromStr) follow the rules
+/// [detected](RuleSet::detect) from their format.
+/// It may also hold [`Edge`] constraints between adjacent cells.
+///
 /// You can modify the cells as you like.
 /// Grids that break the rules will not be solved.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Grid {
     cells: Box<[Cell]>,
-    size: usize,
+    rows: usize,
+    cols: usize,
+    rules: RuleSet,
+    edges: Option<Box<Edges>>,
 }
 
 impl Display for Grid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use std::fmt::Write;
-
-        for row in self.cells.chunks(self.size) {
-            for cell in row {
-                let c = match cell {
-                    Zero => '0',
-                    One => '1',
-                    Empty => '.',
-                };
-                f.write_char(c)?;
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+        self.fmt_cells(f, |f, coord| write!(f, "{}", self[coord].to_char()))
     }
 }
 
@@ -50,13 +59,13 @@ impl Index<(usize, usize)> for Grid {
     type Output = Cell;
 
     fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
-        &self.cells[i * self.size + j]
+        &self.cells[i * self.cols + j]
     }
 }
 
 impl IndexMut<(usize, usize)> for Grid {
     fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
-        &mut self.cells[i * self.size + j]
+        &mut self.cells[i * self.cols + j]
     }
 }
 
@@ -64,59 +73,170 @@ impl FromStr for Grid {
     type Err = GridParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_rules(s, RuleSet::detect(s))
+    }
+}
+
+impl Grid {
+    /// Creates an new empty square grid of a given size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the size is an odd number or 0.
+    pub fn new(size: usize) -> Result<Self, GridSizeError> {
+        Self::with_dimensions(size, size)
+    }
+
+    /// Creates an new empty grid with the given numbers of rows and columns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either dimension is an odd number or 0.
+    pub fn with_dimensions(rows: usize, cols: usize) -> Result<Self, GridSizeError> {
+        Self::with_rules(rows, cols, RuleSet::default())
+    }
+
+    /// Creates an new empty grid with the given numbers of rows and columns,
+    /// following a given set of rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either dimension is 0
+    /// or is not allowed by the rules.
+    pub fn with_rules(rows: usize, cols: usize, rules: RuleSet) -> Result<Self, GridSizeError> {
+        check_dimensions(rows, cols, rules)?;
+        Ok(Self::from_parts(vec![Empty; rows * cols], rows, cols, rules))
+    }
+
+    /// Parses a grid following a given set of rules.
+    ///
+    /// See the [crate documentation](crate) for the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not a valid grid
+    /// or if its dimensions are not allowed by the rules.
+    pub fn parse_with_rules(s: &str, rules: RuleSet) -> Result<Self, GridParseError> {
         use GridParseError::{BadSize, UnexpectedCharacter};
-        use GridSizeError::{EmptyGrid, NotASquare, OddNumberSize};
+        use GridSizeError::{EmptyGrid, NotARectangle};
 
         if s.is_empty() {
             return Err(BadSize(EmptyGrid));
         }
+        if has_edge_markers(s) {
+            return Self::parse_with_edges(s, rules);
+        }
         let lines: Vec<_> = s.lines().collect();
-        let size = lines.len();
-        if size % 2 == 1 {
-            return Err(BadSize(OddNumberSize(size)));
+        let mut cells = Vec::with_capacity(s.len());
+        for c in lines.iter().flat_map(|line| line.chars()) {
+            cells.push(parse_cell(c).ok_or(UnexpectedCharacter(c))?);
         }
-        let mut cells = Vec::with_capacity(size * size);
+        let rows = lines.len();
+        let cols = lines[0].len();
+        check_dimensions(rows, cols, rules)?;
         for (i, line) in lines.iter().enumerate() {
-            let mut count: usize = 0;
-            for c in line.chars() {
-                cells.push(match c {
-                    '0' => Zero,
-                    '1' => One,
-                    '.' => Empty,
-                    _ => return Err(UnexpectedCharacter(c)),
-                });
-                count += 1;
-            }
-            if count != size {
-                return Err(BadSize(NotASquare { line: i + 1, found: count, expected: size }));
+            let found = line.len();
+            if found != cols {
+                return Err(BadSize(NotARectangle { line: i + 1, found, expected: cols }));
             }
         }
-        Ok(Self::from_parts(cells, size))
+        Ok(Self::from_parts(cells, rows, cols, rules))
     }
-}
 
-impl Grid {
-    /// Creates an new empty grid of a given size.
+    /// Returns the number of rows of the array.
+    #[must_use]
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns of the array.
+    #[must_use]
+    pub const fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the number of rows and the number of columns of the array.
+    #[must_use]
+    pub const fn dimensions(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Returns `true` if the grid has as many rows as columns.
+    #[must_use]
+    pub const fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// Returns the set of rules the grid follows.
+    #[must_use]
+    pub const fn rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    /// Returns the constraint between the cell at `coord`
+    /// and the cell to its right, if any.
     ///
-    /// # Errors
+    /// # Panics
     ///
-    /// Returns an error if the size is an odd number or 0.
-    pub fn new(size: usize) -> Result<Self, GridSizeError> {
-        use GridSizeError::{EmptyGrid, OddNumberSize};
+    /// Panics if the coordinates are out of bounds.
+    #[must_use]
+    pub fn edge_right(&self, (i, j): (usize, usize)) -> Option<Edge> {
+        assert!(i < self.rows && j + 1 < self.cols, "edge coordinates out of bounds");
+        self.edges.as_ref().and_then(|edges| edges.right[i * self.cols + j])
+    }
 
-        if size == 0 {
-            Err(EmptyGrid)
-        } else if size % 2 == 1 {
-            Err(OddNumberSize(size))
-        } else {
-            Ok(Self::from_parts(vec![Empty; size * size], size))
-        }
+    /// Returns the constraint between the cell at `coord`
+    /// and the cell below it, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    #[must_use]
+    pub fn edge_below(&self, (i, j): (usize, usize)) -> Option<Edge> {
+        assert!(i + 1 < self.rows && j < self.cols, "edge coordinates out of bounds");
+        self.edges.as_ref().and_then(|edges| edges.below[i * self.cols + j])
+    }
+
+    /// Sets or removes the constraint between the cell at `coord`
+    /// and the cell to its right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn set_edge_right(&mut self, (i, j): (usize, usize), edge: Option<Edge>) {
+        assert!(i < self.rows && j + 1 < self.cols, "edge coordinates out of bounds");
+        let index = i * self.cols + j;
+        self.update_edges(|edges| edges.right[index] = edge);
     }
 
-    /// Returns the number of rows/columns of the array.
+    /// Sets or removes the constraint between the cell at `coord`
+    /// and the cell below it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn set_edge_below(&mut self, (i, j): (usize, usize), edge: Option<Edge>) {
+        assert!(i + 1 < self.rows && j < self.cols, "edge coordinates out of bounds");
+        let index = i * self.cols + j;
+        self.update_edges(|edges| edges.below[index] = edge);
+    }
+
+    /// Returns `true` if the grid holds at least one edge constraint.
     #[must_use]
-    pub const fn size(&self) -> usize {
-        self.size
+    pub const fn has_edges(&self) -> bool {
+        self.edges.is_some()
+    }
+
+    /// Changes the set of rules the grid follows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the grid unchanged,
+    /// if its dimensions are not allowed by the new rules.
+    pub fn set_rules(&mut self, rules: RuleSet) -> Result<(), GridSizeError> {
+        check_dimensions(self.rows, self.cols, rules)?;
+        self.rules = rules;
+        Ok(())
     }
 
     /// Extracts a slice containing the entire underlying array.
@@ -142,7 +262,7 @@ impl Grid {
     /// Returns `true` if the grid is legal.
     #[must_use]
     pub fn is_legal(&self) -> bool {
-        self.check_rule1() && self.check_rule2() && self.check_rule3()
+        self.check_rule1() && self.check_rule2() && self.check_rule3() && self.check_edges()
     }
 
     /// Verifies that a certain cell does not violate any of the rules.
@@ -154,6 +274,7 @@ impl Grid {
             self.check_cell_rule1(coord)
                 && self.check_cell_rule2(coord)
                 && self.check_cell_rule3(coord)
+                && self.check_cell_edges(coord)
         }
     }
 
@@ -163,217 +284,253 @@ impl Grid {
     pub fn next_empty(&self) -> Option<(usize, usize)> {
         for (i, cell) in self.cells.iter().enumerate() {
             if cell.is_empty() {
-                let row = i / self.size;
-                let col = i % self.size;
+                let row = i / self.cols;
+                let col = i % self.cols;
                 return Some((row, col));
             }
         }
         None
     }
-
-    /// Solves the grid using both rules logic and a backtracking algorithm.
-    ///
-    /// Returns an array containing the solution(s), or an empty array if there
-    /// are none.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error before any attempt at solving if
-    /// the grid breaks any of the rules
-    /// (i.e. if [`is_legal`](Grid::is_legal) returns `false`).
-    #[allow(clippy::missing_panics_doc)]
-    pub fn solve(&self) -> Result<Vec<Self>, GridError> {
-        if !self.is_legal() {
-            return Err(GridError::Illegal);
-        }
-        let (mut stack, mut solutions) = (Vec::new(), Vec::new());
-        let mut grid = self.clone();
-        while grid.apply_rules() {}
-        stack.push(grid);
-        while !stack.is_empty() {
-            let mut grid = stack.pop().unwrap();
-            match grid.next_empty() {
-                Some(coord) => {
-                    grid[coord] = One;
-                    if grid.is_cell_legal(coord) {
-                        let mut grid = grid.clone();
-                        while grid.apply_rules() {}
-                        stack.push(grid);
-                    }
-                    grid[coord] = Zero;
-                    if grid.is_cell_legal(coord) {
-                        while grid.apply_rules() {}
-                        stack.push(grid);
-                    }
-                }
-                None => {
-                    if grid.is_legal() {
-                        solutions.push(grid);
-                    }
-                }
-            }
-        }
-        Ok(solutions)
-    }
 }
 
 impl Grid {
-    /// Creates a `Grid` from a `Vec` of `Cell`s
-    /// and the size of the grid.
+    /// Creates a `Grid` from a `Vec` of `Cell`s,
+    /// the dimensions of the grid and its rules.
     ///
     /// # Panics
     ///
     /// Panics if:
     ///
-    /// * size is 0
-    /// * size is odd
-    /// * the number of cells is not size²
-    fn from_parts(cells: Vec<Cell>, size: usize) -> Self {
-        assert!(size != 0, "attempted to create an empty grid");
-        assert!(size % 2 == 0, "attempted to create an odd sized grid");
+    /// * either dimension is 0
+    /// * either dimension is not allowed by the rules
+    /// * the number of cells is not rows × cols
+    fn from_parts(cells: Vec<Cell>, rows: usize, cols: usize, rules: RuleSet) -> Self {
+        assert!(rows != 0 && cols != 0, "attempted to create an empty grid");
+        assert!(
+            rules.allows_length(rows) && rules.allows_length(cols),
+            "attempted to create a grid with a size not allowed by the rules"
+        );
         assert!(
-            cells.len() == size * size,
+            cells.len() == rows * cols,
             "putative grid size does not match the number of cells"
         );
-        Self { cells: cells.into_boxed_slice(), size }
+        Self { cells: cells.into_boxed_slice(), rows, cols, rules, edges: None }
     }
 
-    /// Verifies that the grid abides by rule 1.
-    ///
-    /// Rule 1: no more than two of either number adjacent to each other
-    /// (both vertically and horizontally).
-    fn check_rule1(&self) -> bool {
-        for row in self.cells.chunks(self.size) {
-            for triplet in row.windows(3) {
-                let cell = triplet[0];
-                if cell.is_filled() && cell == triplet[1] && cell == triplet[2] {
-                    return false;
-                }
+    /// Parses a grid in the format with edge constraints, where cells are
+    /// separated by a space or an edge marker, and rows by a line holding
+    /// the markers of the vertical edges below each cell.
+    fn parse_with_edges(s: &str, rules: RuleSet) -> Result<Self, GridParseError> {
+        use GridParseError::{BadSize, MisplacedCharacter, UnexpectedCharacter};
+        use GridSizeError::NotARectangle;
+
+        let mut lines: Vec<_> = s.lines().collect();
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+        if lines.is_empty() {
+            return Err(BadSize(GridSizeError::EmptyGrid));
+        }
+        let unexpected = lines
+            .iter()
+            .flat_map(|line| line.chars())
+            .find(|&c| c != ' ' && parse_cell(c).is_none() && Edge::from_char(c).is_none());
+        if let Some(c) = unexpected {
+            return Err(UnexpectedCharacter(c));
+        }
+        if lines.len() % 2 == 0 {
+            // Edge markers below the last row.
+            let line = lines.len();
+            let (k, found) = lines[line - 1].chars().enumerate().find(|&(_, c)| c != ' ').unwrap();
+            return Err(MisplacedCharacter { line, column: k + 1, found });
+        }
+        let rows = (lines.len() + 1) / 2;
+        let cols = (lines[0].trim_end().chars().count() + 1) / 2;
+        check_dimensions(rows, cols, rules)?;
+        let mut grid = Self::from_parts(vec![Empty; rows * cols], rows, cols, rules);
+        for (l, line) in lines.iter().enumerate().step_by(2) {
+            let found = (line.trim_end().chars().count() + 1) / 2;
+            if found != cols {
+                return Err(BadSize(NotARectangle { line: l + 1, found, expected: cols }));
             }
         }
-        for i in 0..self.size - 2 {
-            for j in 0..self.size {
-                let cell = self[(i, j)];
-                if cell.is_filled() && cell == self[(i + 1, j)] && cell == self[(i + 2, j)] {
-                    return false;
+        for (l, line) in lines.iter().enumerate() {
+            let i = l / 2;
+            for (k, c) in line.trim_end().chars().enumerate() {
+                let misplaced = MisplacedCharacter { line: l + 1, column: k + 1, found: c };
+                let (j, between_cells) = (k / 2, k % 2 == 1);
+                if let Some(cell) = parse_cell(c) {
+                    if l % 2 == 1 || between_cells {
+                        return Err(misplaced);
+                    }
+                    grid[(i, j)] = cell;
+                } else if let Some(edge) = Edge::from_char(c) {
+                    match (l % 2 == 1, between_cells) {
+                        (false, true) if j + 1 < cols => grid.set_edge_right((i, j), Some(edge)),
+                        (true, false) if j < cols => grid.set_edge_below((i, j), Some(edge)),
+                        _ => return Err(misplaced),
+                    }
+                } else if l % 2 == 0 && !between_cells {
+                    return Err(misplaced);
                 }
             }
         }
-        true
+        Ok(grid)
     }
 
-    /// Verifies that the grid abides by rule 2.
+    /// Applies `update` to the edges, allocating them if needed
+    /// and freeing them if no constraint remains.
+    fn update_edges(&mut self, update: impl FnOnce(&mut Edges)) {
+        let len = self.cells.len();
+        let edges = self.edges.get_or_insert_with(|| Box::new(Edges::new(len)));
+        update(edges);
+        if edges.is_empty() {
+            self.edges = None;
+        }
+    }
+
+    /// Writes the grid, using `write_cell` to write each cell.
     ///
-    /// Rule 2: each row and each column should contain an equal number
-    /// of 0s and 1s.
-    fn check_rule2(&self) -> bool {
-        let nmax = self.size / 2;
-        for row in self.cells.chunks(self.size) {
-            let count = row.iter().fold((0, 0), |mut count, cell| {
-                match cell {
-                    Zero => count.0 += 1,
-                    One => count.1 += 1,
-                    Empty => {}
+    /// If the grid has edge constraints, the cells are separated by
+    /// their markers, or spaces, and the rows by lines holding the markers
+    /// of the vertical edges.
+    pub(crate) fn fmt_cells(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        write_cell: impl Fn(&mut fmt::Formatter<'_>, (usize, usize)) -> fmt::Result,
+    ) -> fmt::Result {
+        use std::fmt::Write;
+
+        let Some(edges) = &self.edges else {
+            for i in 0..self.rows {
+                for j in 0..self.cols {
+                    write_cell(f, (i, j))?;
                 }
-                count
-            });
-            if count.0 > nmax || count.1 > nmax {
-                return false;
+                writeln!(f)?;
             }
-        }
-        for i in 0..self.size {
-            let mut count = (0, 0);
-            for j in 0..self.size {
-                match self[(j, i)] {
-                    Zero => count.0 += 1,
-                    One => count.1 += 1,
-                    Empty => {}
+            return Ok(());
+        };
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                if j != 0 {
+                    f.write_char(edges.right[i * self.cols + j - 1].map_or(' ', Edge::to_char))?;
                 }
+                write_cell(f, (i, j))?;
             }
-            if count.0 > nmax || count.1 > nmax {
-                return false;
+            writeln!(f)?;
+            if i + 1 != self.rows {
+                let markers = (0..self.cols)
+                    .map(|j| edges.below[i * self.cols + j].map_or(' ', Edge::to_char))
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                writeln!(f, "{}", markers.trim_end())?;
             }
         }
-        true
+        Ok(())
+    }
+
+    /// Verifies that the grid abides by rule 1.
+    ///
+    /// Rule 1: no more than two (or the maximum run length of the rule set)
+    /// of either number adjacent to each other (both vertically and
+    /// horizontally).
+    fn check_rule1(&self) -> bool {
+        let Some(max_run) = self.rules.max_run() else { return true };
+        self.iter_rows().chain(self.iter_cols()).all(|line| line.longest_run() <= max_run)
+    }
+
+    /// Verifies that the grid abides by rule 2.
+    ///
+    /// Rule 2: each row and each column should contain an equal number
+    /// of 0s and 1s (or, with relaxed balance, numbers differing by one
+    /// at most).
+    fn check_rule2(&self) -> bool {
+        self.iter_rows().chain(self.iter_cols()).all(|line| self.is_balanced(line))
     }
 
     /// Verifies that the grid abides by rule 3.
     ///
     /// Rule 3: no two rows and no two columns can be the same.
+    /// Can be disabled by the rule set.
     fn check_rule3(&self) -> bool {
-        for i in 0..self.size - 1 {
-            for j in i + 1..self.size {
-                if (0..self.size).all(|k| self[(i, k)].is_filled() && self[(i, k)] == self[(j, k)])
-                {
-                    return false;
-                }
-                if (0..self.size).all(|k| self[(k, i)].is_filled() && self[(k, i)] == self[(k, j)])
-                {
-                    return false;
-                }
-            }
-        }
-        true
+        !self.rules.unique_lines()
+            || (are_unique(self.rows, |i| self.row(i)) && are_unique(self.cols, |j| self.col(j)))
+    }
+
+    /// Verifies that the grid abides by its edge constraints.
+    fn check_edges(&self) -> bool {
+        self.edges.is_none()
+            || self.iter_rows().chain(self.iter_cols()).all(|line| {
+                (1..line.len()).all(|k| {
+                    line.edge_after(k - 1).map_or(true, |edge| edge.holds(line[k - 1], line[k]))
+                })
+            })
     }
 
     /// Verifies that the cell with the given coordinates abides by rule 1.
     ///
-    /// Rule 1: no more than two of either number adjacent to each other
-    /// (both vertically and horizontally).
+    /// Rule 1: no more than two (or the maximum run length of the rule set)
+    /// of either number adjacent to each other (both vertically and
+    /// horizontally).
     fn check_cell_rule1(&self, (row, col): (usize, usize)) -> bool {
-        use std::cmp::min;
-
-        for i in row.saturating_sub(2)..min(row + 1, self.size - 2) {
-            let cell = self[(i, col)];
-            if cell.is_filled() && cell == self[(i + 1, col)] && cell == self[(i + 2, col)] {
-                return false;
-            }
-        }
-        for j in col.saturating_sub(2)..min(col + 1, self.size - 2) {
-            let cell = self[(row, j)];
-            if cell.is_filled() && cell == self[(row, j + 1)] && cell == self[(row, j + 2)] {
-                return false;
-            }
-        }
-        true
+        let Some(max_run) = self.rules.max_run() else { return true };
+        self.row(row).run_at(col) <= max_run && self.col(col).run_at(row) <= max_run
     }
 
     /// Verifies that the cell with the given coordinates abides by rule 2.
     ///
     /// Rule 2: each row and each column should contain an equal number
-    /// of 0s and 1s.
+    /// of 0s and 1s (or, with relaxed balance, numbers differing by one
+    /// at most).
     fn check_cell_rule2(&self, (row, col): (usize, usize)) -> bool {
-        let nmax = self.size / 2;
-        let mut count = (0, 0, 0, 0);
-        for k in 0..self.size {
-            match self[(row, k)] {
-                Zero => count.0 += 1,
-                One => count.1 += 1,
-                Empty => {}
-            }
-            match self[(k, col)] {
-                Zero => count.2 += 1,
-                One => count.3 += 1,
-                Empty => {}
-            }
-        }
-        count.0 <= nmax && count.1 <= nmax && count.2 <= nmax && count.3 <= nmax
+        self.is_balanced(self.row(row)) && self.is_balanced(self.col(col))
+    }
+
+    /// Returns `true` if a line does not have too many `0`s or `1`s
+    /// for rule 2.
+    fn is_balanced(&self, line: Line<'_>) -> bool {
+        let nmax = self.rules.max_count(line.len());
+        line.zeros() <= nmax && line.ones() <= nmax
     }
 
     /// Verifies that the cell with the given coordinates abides by rule 3.
     ///
     /// Rule 3: no two rows and no two columns can be the same.
+    /// Can be disabled by the rule set.
     fn check_cell_rule3(&self, (row, col): (usize, usize)) -> bool {
-        let rows_abide =
-            (0..self.size).filter(|&i| i != row && self[(i, col)] == self[(row, col)]).all(|i| !{
-                (0..self.size).all(|j| self[(row, j)].is_filled() && self[(row, j)] == self[(i, j)])
-            });
-        let cols_abide =
-            (0..self.size).filter(|&j| j != col && self[(row, j)] == self[(row, col)]).all(|j| !{
-                (0..self.size).all(|i| self[(i, col)].is_filled() && self[(i, col)] == self[(i, j)])
-            });
+        if !self.rules.unique_lines() {
+            return true;
+        }
+        let (row_line, col_line) = (self.row(row), self.col(col));
+        let rows_abide = !row_line.is_filled()
+            || self.iter_rows().enumerate().all(|(i, other)| i == row || other != row_line);
+        let cols_abide = !col_line.is_filled()
+            || self.iter_cols().enumerate().all(|(j, other)| j == col || other != col_line);
         rows_abide && cols_abide
     }
+
+    /// Verifies that the cell with the given coordinates abides by its edge
+    /// constraints.
+    fn check_cell_edges(&self, coord: (usize, usize)) -> bool {
+        self.edges.is_none()
+            || self.neighbours(coord).all(|(other, edge)| edge.holds(self[coord], self[other]))
+    }
+
+    /// Returns the cells linked to the cell at `coord` by an edge constraint,
+    /// with that constraint.
+    fn neighbours(
+        &self,
+        (i, j): (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), Edge)> + '_ {
+        let left = (j > 0).then(|| ((i, j - 1), self.edge_right((i, j - 1))));
+        let right = (j + 1 < self.cols).then(|| ((i, j + 1), self.edge_right((i, j))));
+        let above = (i > 0).then(|| ((i - 1, j), self.edge_below((i - 1, j))));
+        let below = (i + 1 < self.rows).then(|| ((i + 1, j), self.edge_below((i, j))));
+        IntoIterator::into_iter([left, right, above, below])
+            .flatten()
+            .filter_map(|(other, edge)| edge.map(|edge| (other, edge)))
+    }
 }
 
 impl Grid {
@@ -382,7 +539,7 @@ impl Grid {
     /// then returns if the grid was modified or repeats the operation
     /// for the next rule. Each rule is applied once at the most.
     ///
-    /// Returns `true` if the grid was modified.
+    /// Returns the rule that modified the grid, if any.
     ///
     /// # Warning
     ///
@@ -391,40 +548,141 @@ impl Grid {
     /// checked for legality against the other rules. This can result in
     /// grids with no legal solution being filled illegally.
     /// Grids with one or more legal solution(s) are not affected.
-    fn apply_rules(&mut self) -> bool {
-        self.apply_rule1() || self.apply_rule2() || self.apply_rule3()
+    pub fn apply_rules(&mut self) -> Option<Rule> {
+        Rule::ALL.iter().copied().find(|&rule| self.apply_rule(rule))
+    }
+
+    /// Skims through the grid once, filling in the blanks
+    /// where the value is unambiguous according to a given rule.
+    ///
+    /// Returns `true` if the grid was modified.
+    ///
+    /// # Warning
+    ///
+    /// Does not guarantee the legality of the modifications,
+    /// as for [`apply_rules`](Grid::apply_rules).
+    pub fn apply_rule(&mut self, rule: Rule) -> bool {
+        match rule {
+            Rule::Rule1 => self.apply_rule1(),
+            Rule::Rule2 => self.apply_rule2(),
+            Rule::Rule3 => self.apply_rule3(),
+            Rule::Edges => self.apply_edges(),
+            Rule::Lookahead => self.apply_lookahead(),
+        }
+    }
+
+    /// Disambiguates empty cells after the edge constraints.
+    fn apply_edges(&mut self) -> bool {
+        if self.edges.is_none() {
+            return false;
+        }
+        let mut rule_applied = false;
+        for i in 0..self.rows {
+            rule_applied |= self.apply_edges_line(|grid| grid.row(i), |k| (i, k));
+        }
+        for j in 0..self.cols {
+            rule_applied |= self.apply_edges_line(|grid| grid.col(j), |k| (k, j));
+        }
+        rule_applied
+    }
+
+    /// Disambiguates the empty cells of a line after the edge constraints
+    /// between its cells.
+    ///
+    /// `line` returns the line and `coord` the coordinates of its `k`th
+    /// cell. The line is taken again after each cell filled, so that the
+    /// numbers carry along chains of constraints.
+    fn apply_edges_line(
+        &mut self,
+        line: impl Fn(&Self) -> Line<'_>,
+        coord: impl Fn(usize) -> (usize, usize),
+    ) -> bool {
+        let mut rule_applied = false;
+        for k in 1..line(self).len() {
+            let line = line(self);
+            let Some(edge) = line.edge_after(k - 1) else { continue };
+            let (empty, value) = match (line[k - 1], line[k]) {
+                (Empty, cell) if cell.is_filled() => (k - 1, edge.other(cell)),
+                (cell, Empty) if cell.is_filled() => (k, edge.other(cell)),
+                _ => continue,
+            };
+            self[coord(empty)] = value;
+            rule_applied = true;
+        }
+        rule_applied
     }
 
     /// Disambiguates empty cells after rule 1.
     ///
-    /// Rule 1: no more than two of either number adjacent to each other
-    /// (both vertically and horizontally).
-    #[rustfmt::skip]
+    /// Rule 1: no more than two (or the maximum run length of the rule set)
+    /// of either number adjacent to each other (both vertically and
+    /// horizontally).
     fn apply_rule1(&mut self) -> bool {
+        let Some(max_run) = self.rules.max_run() else { return false };
         let mut rule_applied = false;
-        for i in 0..self.size {
-            for j in 0..self.size - 2 {
-                let trio = (self[(i, j)], self[(i, j + 1)], self[(i, j + 2)]);
-                match trio {
-                    (Empty, Zero, Zero) => { self[(i, j  )] = One;  rule_applied = true; }
-                    (Zero, Empty, Zero) => { self[(i, j+1)] = One;  rule_applied = true; }
-                    (Zero, Zero, Empty) => { self[(i, j+2)] = One;  rule_applied = true; }
-                    (Empty, One, One)   => { self[(i, j  )] = Zero; rule_applied = true; }
-                    (One, Empty, One)   => { self[(i, j+1)] = Zero; rule_applied = true; }
-                    (One, One, Empty)   => { self[(i, j+2)] = Zero; rule_applied = true; }
-                    _ => {},
-                }
-                let trio = (self[(j, i)], self[(j + 1, i)], self[(j + 2, i)]);
-                match trio {
-                    (Empty, Zero, Zero) => { self[(j  , i)] = One;  rule_applied = true; }
-                    (Zero, Empty, Zero) => { self[(j+1, i)] = One;  rule_applied = true; }
-                    (Zero, Zero, Empty) => { self[(j+2, i)] = One;  rule_applied = true; }
-                    (Empty, One, One)   => { self[(j  , i)] = Zero; rule_applied = true; }
-                    (One, Empty, One)   => { self[(j+1, i)] = Zero; rule_applied = true; }
-                    (One, One, Empty)   => { self[(j+2, i)] = Zero; rule_applied = true; }
-                    _ => {},
+        for i in 0..self.rows {
+            rule_applied |= self.apply_rule1_line(|grid| grid.row(i), |k| (i, k), max_run);
+        }
+        for j in 0..self.cols {
+            rule_applied |= self.apply_rule1_line(|grid| grid.col(j), |k| (k, j), max_run);
+        }
+        rule_applied
+    }
+
+    /// Disambiguates the empty cells of a line after rule 1.
+    ///
+    /// `line` returns the line and `coord` the coordinates of its `k`th
+    /// cell. Fills the only empty cell of every run of `max_run + 1` cells
+    /// whose other cells all hold the same number.
+    fn apply_rule1_line(
+        &mut self,
+        line: impl Fn(&Self) -> Line<'_>,
+        coord: impl Fn(usize) -> (usize, usize),
+        max_run: usize,
+    ) -> bool {
+        if max_run == 2 {
+            return self.apply_rule1_line_trios(line, coord);
+        }
+        let mut rule_applied = false;
+        'runs: for first in 0..line(self).len().saturating_sub(max_run) {
+            let line = line(self);
+            let (mut empty, mut value) = (None, Empty);
+            for k in first..=first + max_run {
+                match line[k] {
+                    Empty if empty.is_none() => empty = Some(k),
+                    cell if cell.is_filled() && (value.is_empty() || value == cell) => value = cell,
+                    _ => continue 'runs,
                 }
             }
+            if let Some(k) = empty {
+                self[coord(k)] = !value;
+                rule_applied = true;
+            }
+        }
+        rule_applied
+    }
+
+    /// Same as [`apply_rule1_line`](Grid::apply_rule1_line) with runs of two
+    /// at most, the rule of the classic takuzu. Much faster.
+    fn apply_rule1_line_trios(
+        &mut self,
+        line: impl Fn(&Self) -> Line<'_>,
+        coord: impl Fn(usize) -> (usize, usize),
+    ) -> bool {
+        let mut rule_applied = false;
+        for k in 0..line(self).len().saturating_sub(2) {
+            let line = line(self);
+            let (empty, value) = match (line[k], line[k + 1], line[k + 2]) {
+                (Empty, Zero, Zero) => (k, One),
+                (Zero, Empty, Zero) => (k + 1, One),
+                (Zero, Zero, Empty) => (k + 2, One),
+                (Empty, One, One) => (k, Zero),
+                (One, Empty, One) => (k + 1, Zero),
+                (One, One, Empty) => (k + 2, Zero),
+                _ => continue,
+            };
+            self[coord(empty)] = value;
+            rule_applied = true;
         }
         rule_applied
     }
@@ -432,111 +690,201 @@ impl Grid {
     /// Disambiguates empty cells after rule 2.
     ///
     /// Rule 2: each row and each column should contain an equal number
-    /// of 0s and 1s.
+    /// of 0s and 1s (or, with relaxed balance, numbers differing by one
+    /// at most).
     fn apply_rule2(&mut self) -> bool {
         let mut rule_applied = false;
-        let nmax = self.size / 2;
-        for i in 0..self.size {
-            let mut count = (0, 0, 0, 0);
-            for j in 0..self.size {
-                match self[(i, j)] {
-                    Zero => count.0 += 1,
-                    One => count.1 += 1,
-                    Empty => {}
-                }
-                match self[(j, i)] {
-                    Zero => count.2 += 1,
-                    One => count.3 += 1,
-                    Empty => {}
-                }
-            }
-            if count.0 == nmax && count.1 != nmax {
-                rule_applied = true;
-                for j in 0..self.size {
+        for i in 0..self.rows {
+            if let Some(value) = self.rule2_value(self.row(i)) {
+                for j in 0..self.cols {
                     if self[(i, j)].is_empty() {
-                        self[(i, j)] = One;
+                        self[(i, j)] = value;
+                        rule_applied = true;
                     }
                 }
-            } else if count.1 == nmax && count.0 != nmax {
-                rule_applied = true;
-                for j in 0..self.size {
+            }
+        }
+        for j in 0..self.cols {
+            if let Some(value) = self.rule2_value(self.col(j)) {
+                for i in 0..self.rows {
                     if self[(i, j)].is_empty() {
-                        self[(i, j)] = Zero;
+                        self[(i, j)] = value;
+                        rule_applied = true;
                     }
                 }
             }
-            if count.2 == nmax && count.3 != nmax {
-                rule_applied = true;
-                for j in 0..self.size {
-                    if self[(j, i)].is_empty() {
-                        self[(j, i)] = One;
-                    }
-                }
-            } else if count.3 == nmax && count.2 != nmax {
-                rule_applied = true;
-                for j in 0..self.size {
-                    if self[(j, i)].is_empty() {
-                        self[(j, i)] = Zero;
-                    }
-                }
+        }
+        rule_applied
+    }
+
+    /// Returns the number the empty cells of a line must hold after rule 2,
+    /// if the line holds as many of the other number as it can.
+    fn rule2_value(&self, line: Line<'_>) -> Option<Cell> {
+        let nmax = self.rules.max_count(line.len());
+        let (zeros, ones) = (line.zeros(), line.ones());
+        if zeros == nmax && ones != nmax {
+            Some(One)
+        } else if ones == nmax && zeros != nmax {
+            Some(Zero)
+        } else {
+            None
+        }
+    }
+
+    /// Disambiguates empty cells after the completions of each line.
+    ///
+    /// Finds the ways to complete each line abiding by rules 1 and 2, by
+    /// the edge constraints along the line and, if enabled, by rule 3,
+    /// and fills the cells that hold the same number in all of them.
+    /// This subsumes the other rules, but is much slower, so it should be
+    /// applied after them.
+    fn apply_lookahead(&mut self) -> bool {
+        let mut rule_applied = false;
+        for i in 0..self.rows {
+            let deductions = self.lookahead_line(self.row(i), self.iter_rows());
+            for &(k, value) in &deductions {
+                self[(i, k)] = value;
+            }
+            rule_applied |= !deductions.is_empty();
+        }
+        for j in 0..self.cols {
+            let deductions = self.lookahead_line(self.col(j), self.iter_cols());
+            for &(k, value) in &deductions {
+                self[(k, j)] = value;
             }
+            rule_applied |= !deductions.is_empty();
         }
         rule_applied
     }
 
+    /// Returns the empty cells of a line that hold the same number in all
+    /// its completions, by their index in the line, with that number.
+    ///
+    /// `parallels` are the lines parallel to it, itself included,
+    /// that it must differ from once completed if rule 3 is enabled.
+    fn lookahead_line<'a>(
+        &self,
+        line: Line<'_>,
+        parallels: impl Iterator<Item = Line<'a>>,
+    ) -> Vec<(usize, Cell)> {
+        let len = line.len();
+        if line.is_filled() {
+            return Vec::new();
+        }
+        // Rule 3: the completed lines parallel to this one are excluded.
+        let completed: Vec<Vec<_>> = if self.rules.unique_lines() {
+            parallels.filter(Line::is_filled).map(|other| other.iter().collect()).collect()
+        } else {
+            Vec::new()
+        };
+        // The other rules are symmetric: an empty line has no deduction to offer.
+        if completed.is_empty() && line.empties() == len {
+            return Vec::new();
+        }
+        let edges: Vec<_> = (0..len - 1).map(|k| line.edge_after(k)).collect();
+        let rules = lookahead::LineRules {
+            max_run: self.rules.max_run().map_or(len, |max_run| max_run.min(len)),
+            max_count: self.rules.max_count(len),
+        };
+        let cells: Vec<_> = line.iter().collect();
+        let completed: Vec<_> = completed.iter().map(Vec::as_slice).collect();
+        let candidates = lookahead::candidates(&cells, &edges, &rules, &completed);
+        let deductions = candidates.into_iter().enumerate().filter(|&(k, _)| line[k].is_empty());
+        deductions
+            .filter_map(|(k, candidates)| match candidates {
+                [true, false] => Some((k, Zero)),
+                [false, true] => Some((k, One)),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Disambiguates empty cells after rule 3.
     ///
     /// Rule 3: no two rows and no two columns can be the same.
+    ///
+    /// Can be disabled by the rule set. Only applies to lines in which rule 2
+    /// requires equal numbers of 0s and 1s.
     fn apply_rule3(&mut self) -> bool {
-        macro_rules! row {
-            ($i:expr) => {
-                self.cells[$i * self.size..($i + 1) * self.size]
-            };
+        if !self.rules.unique_lines() {
+            return false;
         }
-
-        let size = self.size;
+        let (rows, cols) = (self.rows, self.cols);
         let mut rule_applied = false;
-        for i in 0..size {
-            if row!(i).iter().filter(|value| value.is_empty()).count() == 2 {
-                for l in 0..size {
-                    if l != i
-                        && !row!(l).contains(&Empty)
-                        && row!(i)
-                            .iter()
-                            .zip(row!(l).iter())
-                            .filter(|&(value, _)| value.is_filled())
-                            .all(|(value, other)| value == other)
-                    {
-                        for j in 0..size {
-                            if self[(i, j)].is_empty() {
-                                self[(i, j)] = !self[(l, j)];
-                            }
+        if 2 * self.rules.max_count(cols) == cols {
+            for i in 0..rows {
+                if let Some(l) = twin(self.row(i), self.iter_rows()) {
+                    for j in 0..cols {
+                        if self[(i, j)].is_empty() {
+                            self[(i, j)] = !self[(l, j)];
                         }
-                        rule_applied = true;
-                        break;
                     }
+                    rule_applied = true;
                 }
             }
-            let j = i;
-            if (0..size).filter(|&l| self[(l, j)].is_empty()).count() == 2 {
-                for m in 0..size {
-                    if m != j
-                        && (0..size).all(|i| self[(i, m)].is_filled())
-                        && (0..size)
-                            .filter(|&i| self[(i, j)].is_filled())
-                            .all(|i| self[(i, j)] == self[(i, m)])
-                    {
-                        for i in 0..size {
-                            if self[(i, j)].is_empty() {
-                                self[(i, j)] = !self[(i, m)];
-                            }
+        }
+        if 2 * self.rules.max_count(rows) == rows {
+            for j in 0..cols {
+                if let Some(m) = twin(self.col(j), self.iter_cols()) {
+                    for i in 0..rows {
+                        if self[(i, j)].is_empty() {
+                            self[(i, j)] = !self[(i, m)];
                         }
-                        rule_applied = true;
-                        break;
                     }
+                    rule_applied = true;
                 }
             }
         }
         rule_applied
     }
 }
+
+/// Returns `true` if no two of the `count` lines returned by `line`
+/// are the same completed line.
+fn are_unique<'a>(count: usize, line: impl Fn(usize) -> Line<'a>) -> bool {
+    (0..count).all(|k| {
+        let first = line(k);
+        !first.is_filled() || (k + 1..count).all(|l| line(l) != first)
+    })
+}
+
+/// Returns the index of the completed line, among `others`, that a line
+/// with two empty cells would become if it were filled like it.
+/// Rule 3 then requires the empty cells to hold the other numbers.
+fn twin<'a>(line: Line<'a>, mut others: impl Iterator<Item = Line<'a>>) -> Option<usize> {
+    if line.empties() != 2 {
+        return None;
+    }
+    others.position(|other| other.is_filled() && line.matches(other))
+}
+
+/// Verifies that a grid with the given dimensions can follow the rules.
+const fn check_dimensions(rows: usize, cols: usize, rules: RuleSet) -> Result<(), GridSizeError> {
+    use GridSizeError::{EmptyGrid, OddNumberOfColumns, OddNumberOfRows};
+
+    if rows == 0 || cols == 0 {
+        Err(EmptyGrid)
+    } else if !rules.allows_length(rows) {
+        Err(OddNumberOfRows(rows))
+    } else if !rules.allows_length(cols) {
+        Err(OddNumberOfColumns(cols))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the cell represented by `c`, if any.
+const fn parse_cell(c: char) -> Option<Cell> {
+    match c {
+        '0' => Some(Zero),
+        '1' => Some(One),
+        '.' => Some(Empty),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `s` holds edge markers, i.e. is written in the format
+/// with edge constraints.
+fn has_edge_markers(s: &str) -> bool {
+    s.contains(['=', 'x', '×'])
+}
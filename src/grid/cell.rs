@@ -1,22 +1,17 @@
-use std::{default::Default, ops::Not};
+use std::ops::Not;
 
 use Cell::{Empty, One, Zero};
 
 ///  An enum representing the state of a cell.
 #[allow(missing_docs)]
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Cell {
     Zero,
     One,
+    #[default]
     Empty,
 }
 
-impl Default for Cell {
-    fn default() -> Self {
-        Empty
-    }
-}
-
 impl Not for Cell {
     type Output = Self;
 
@@ -41,4 +36,14 @@ impl Cell {
     pub const fn is_filled(self) -> bool {
         !matches!(self, Empty)
     }
+
+    /// Returns the character representing the cell: `0`, `1` or `.`.
+    #[must_use]
+    pub const fn to_char(self) -> char {
+        match self {
+            Zero => '0',
+            One => '1',
+            Empty => '.',
+        }
+    }
 }
@@ -0,0 +1,73 @@
+use crate::Cell;
+
+/// A constraint between two adjacent cells, as in the "Tango" variant.
+///
+/// In the text format, `Equal` is represented by `=`
+/// and `Opposite` by `x` (or `×`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Edge {
+    /// Both cells hold the same number.
+    Equal,
+    /// The cells hold different numbers.
+    Opposite,
+}
+
+impl Edge {
+    /// Returns the edge represented by `c`, if any.
+    pub(crate) const fn from_char(c: char) -> Option<Self> {
+        match c {
+            '=' => Some(Self::Equal),
+            'x' | '×' => Some(Self::Opposite),
+            _ => None,
+        }
+    }
+
+    /// Returns the character representing the edge.
+    #[must_use]
+    pub const fn to_char(self) -> char {
+        match self {
+            Self::Equal => '=',
+            Self::Opposite => 'x',
+        }
+    }
+
+    /// Returns `false` if the cells are both filled and violate the constraint.
+    #[must_use]
+    pub fn holds(self, a: Cell, b: Cell) -> bool {
+        a.is_empty() || b.is_empty() || self.other(a) == b
+    }
+
+    /// Returns the value the other cell must hold if one of them holds `cell`.
+    #[must_use]
+    pub fn other(self, cell: Cell) -> Cell {
+        match self {
+            Self::Equal => cell,
+            Self::Opposite => !cell,
+        }
+    }
+}
+
+/// The edges of a grid.
+///
+/// `right[i * cols + j]` is the edge between `(i, j)` and `(i, j + 1)`,
+/// `below[i * cols + j]` the edge between `(i, j)` and `(i + 1, j)`.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Edges {
+    pub right: Box<[Option<Edge>]>,
+    pub below: Box<[Option<Edge>]>,
+}
+
+impl Edges {
+    /// Creates a set of edges for a grid of `len` cells, all unconstrained.
+    pub fn new(len: usize) -> Self {
+        Self {
+            right: vec![None; len].into_boxed_slice(),
+            below: vec![None; len].into_boxed_slice(),
+        }
+    }
+
+    /// Returns `true` if there is no edge constraint at all.
+    pub fn is_empty(&self) -> bool {
+        self.right.iter().chain(self.below.iter()).all(Option::is_none)
+    }
+}
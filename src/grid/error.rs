@@ -10,11 +10,23 @@ use std::{
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
 pub enum GridParseError {
     /// The grid does not have the right size.
-    /// It should be square, of non-null, even size.
+    /// It should be rectangular, with a non-null, even number of rows
+    /// and columns.
     BadSize(GridSizeError),
     /// At least one character other than `0`, `1`, `.` or `\n`
+    /// (or, for grids with edge constraints, ` `, `=`, `x` or `×`)
     /// was found in the string.
     UnexpectedCharacter(char),
+    /// In a grid with edge constraints, a cell was found where an edge
+    /// marker was expected, or the reverse.
+    MisplacedCharacter {
+        /// Line on which the character was found.
+        line: usize,
+        /// Position of the character in the line.
+        column: usize,
+        /// The misplaced character.
+        found: char,
+    },
 }
 
 impl Error for GridParseError {}
@@ -22,9 +34,12 @@ impl Error for GridParseError {}
 impl Display for GridParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
-            GridParseError::BadSize(e) => write!(f, "bad grid size: {}", e),
-            GridParseError::UnexpectedCharacter(c) => {
-                write!(f, "found unexpected character `{}`", c)
+            Self::BadSize(e) => write!(f, "bad grid size: {e}"),
+            Self::UnexpectedCharacter(c) => {
+                write!(f, "found unexpected character `{c}`")
+            }
+            Self::MisplacedCharacter { line, column, found } => {
+                write!(f, "found misplaced character `{found}` (line {line}, column {column})")
             }
         }
     }
@@ -48,7 +63,7 @@ impl Error for GridError {}
 impl Display for GridError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
-            GridError::Illegal => write!(f, "grid is illegal"),
+            Self::Illegal => write!(f, "grid is illegal"),
         }
     }
 }
@@ -58,8 +73,8 @@ impl Display for GridError {
 pub enum GridSizeError {
     /// The grid is empty.
     EmptyGrid,
-    /// The grid is not a square.
-    NotASquare {
+    /// The rows of the grid do not all have the same length.
+    NotARectangle {
         /// Line on which the error occured.
         line: usize,
         /// Number of characters found.
@@ -67,9 +82,12 @@ pub enum GridSizeError {
         /// Number of characters expected.
         expected: usize,
     },
-    /// The size of the grid is an odd number.
-    /// The field contains the number of lines in the grid.
-    OddNumberSize(usize),
+    /// The number of rows of the grid is odd and the rules require it to be even.
+    /// The field contains the number of rows in the grid.
+    OddNumberOfRows(usize),
+    /// The number of columns of the grid is odd and the rules require it to be even.
+    /// The field contains the number of columns in the grid.
+    OddNumberOfColumns(usize),
 }
 
 impl Error for GridSizeError {}
@@ -77,16 +95,18 @@ impl Error for GridSizeError {}
 impl Display for GridSizeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
-            GridSizeError::EmptyGrid => write!(f, "grid is empty"),
-            GridSizeError::NotASquare { line, found, expected } => {
+            Self::EmptyGrid => write!(f, "grid is empty"),
+            Self::NotARectangle { line, found, expected } => {
                 write!(
                     f,
-                    "grid is not a square (line {}, expected {} characters, found {})",
-                    line, expected, found
+                    "grid is not a rectangle (line {line}, expected {expected} characters, found {found})"
                 )
             }
-            GridSizeError::OddNumberSize(n) => {
-                write!(f, "grid size is an odd number ({} lines found)", n)
+            Self::OddNumberOfRows(n) => {
+                write!(f, "number of rows is odd ({n} lines found)")
+            }
+            Self::OddNumberOfColumns(n) => {
+                write!(f, "number of columns is odd ({n} characters per line found)")
             }
         }
     }
@@ -0,0 +1,244 @@
+use std::{
+    fmt::{self, Display},
+    iter::{Copied, StepBy, Take},
+    ops::Index,
+    slice,
+};
+
+use super::{
+    cell::Cell::{self, Empty, One, Zero},
+    edge::Edge,
+    Grid,
+};
+
+/// A view of a row or a column of a grid.
+///
+/// Created by [`Grid::row`], [`Grid::col`], [`Grid::iter_rows`]
+/// and [`Grid::iter_cols`]. Lines compare equal if they hold the same cells,
+/// whatever the edge constraints between them.
+///
+/// ```
+/// use takuzu::{Balance, Grid, RuleSet};
+///
+/// let rules = RuleSet::TAKUZU.with_balance(Balance::Relaxed);
+/// let grid = Grid::parse_with_rules("0.1\n011\n", rules).unwrap();
+/// assert_eq!(grid.row(1).to_string(), "011");
+/// assert_eq!((grid.row(1).zeros(), grid.row(1).ones()), (1, 2));
+/// assert_eq!(grid.row(1).longest_run(), 2);
+/// assert!(grid.row(0).matches(grid.row(1)));
+/// assert_eq!(grid.iter_cols().filter(|col| col.is_filled()).count(), 2);
+/// ```
+#[derive(Clone, Copy)]
+pub struct Line<'a> {
+    cells: &'a [Cell],
+    /// The edges between each cell and the next one, laid out as the cells.
+    edges: Option<&'a [Option<Edge>]>,
+    step: usize,
+    len: usize,
+}
+
+impl<'a> Line<'a> {
+    /// Returns the line of `len` cells starting at `start` in `cells`,
+    /// `step` cells apart, with the edges at the same places in `edges`.
+    fn new(
+        cells: &'a [Cell],
+        edges: Option<&'a [Option<Edge>]>,
+        start: usize,
+        step: usize,
+        len: usize,
+    ) -> Self {
+        let range = start..=start + (len - 1) * step;
+        Self { cells: &cells[range.clone()], edges: edges.map(|edges| &edges[range]), step, len }
+    }
+
+    /// Returns the number of cells in the line.
+    #[allow(clippy::len_without_is_empty)] // Lines are never empty.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the `k`th cell of the line, or `None` if out of bounds.
+    #[must_use]
+    pub fn get(&self, k: usize) -> Option<Cell> {
+        (k < self.len).then(|| self.cells[k * self.step])
+    }
+
+    /// Returns the constraint between the `k`th cell and the next one, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k + 1` is out of bounds.
+    #[must_use]
+    pub fn edge_after(&self, k: usize) -> Option<Edge> {
+        assert!(k + 1 < self.len, "edge index out of bounds");
+        self.edges.and_then(|edges| edges[k * self.step])
+    }
+
+    /// Returns an iterator over the cells of the line.
+    pub fn iter(&self) -> LineIter<'a> {
+        self.cells.iter().step_by(self.step).take(self.len).copied()
+    }
+
+    /// Returns the number of `0`s in the line.
+    #[must_use]
+    pub fn zeros(&self) -> usize {
+        self.count(Zero)
+    }
+
+    /// Returns the number of `1`s in the line.
+    #[must_use]
+    pub fn ones(&self) -> usize {
+        self.count(One)
+    }
+
+    /// Returns the number of empty cells in the line.
+    #[must_use]
+    pub fn empties(&self) -> usize {
+        self.count(Empty)
+    }
+
+    /// Returns the number of cells of the line holding `cell`.
+    #[must_use]
+    pub fn count(&self, cell: Cell) -> usize {
+        self.iter().filter(|&other| other == cell).count()
+    }
+
+    /// Returns `true` if the line contains no `Empty` cell.
+    #[must_use]
+    pub fn is_filled(&self) -> bool {
+        self.iter().all(Cell::is_filled)
+    }
+
+    /// Returns the length of the longest run of adjacent cells
+    /// holding the same number, or 0 if all the cells are empty.
+    #[must_use]
+    pub fn longest_run(&self) -> usize {
+        let runs = self.iter().scan((Empty, 0), |(previous, len), cell| {
+            *len = if cell == *previous { *len + 1 } else { 1 };
+            *previous = cell;
+            Some(if cell.is_filled() { *len } else { 0 })
+        });
+        runs.max().unwrap_or(0)
+    }
+
+    /// Returns the length of the run of adjacent cells holding the same
+    /// number as the `k`th cell, or 0 if that cell is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is out of bounds.
+    #[must_use]
+    pub fn run_at(&self, k: usize) -> usize {
+        assert!(k < self.len, "cell index out of bounds");
+        let cell = self[k];
+        if cell.is_empty() {
+            return 0;
+        }
+        let before = (0..k).rev().take_while(|&l| self[l] == cell).count();
+        let after = (k + 1..self.len).take_while(|&l| self[l] == cell).count();
+        before + 1 + after
+    }
+
+    /// Returns `true` if the numbers of the line are found at the same
+    /// places in `other`. Empty cells match any cell.
+    #[must_use]
+    pub fn matches(&self, other: Line<'_>) -> bool {
+        self.len == other.len
+            && self.iter().zip(other.iter()).all(|(cell, other)| cell.is_empty() || cell == other)
+    }
+}
+
+/// An iterator over the cells of a [`Line`].
+pub type LineIter<'a> = Copied<Take<StepBy<slice::Iter<'a, Cell>>>>;
+
+impl Index<usize> for Line<'_> {
+    type Output = Cell;
+
+    fn index(&self, k: usize) -> &Self::Output {
+        assert!(k < self.len, "cell index out of bounds");
+        &self.cells[k * self.step]
+    }
+}
+
+impl<'a> IntoIterator for Line<'a> {
+    type Item = Cell;
+    type IntoIter = LineIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &Line<'a> {
+    type Item = Cell;
+    type IntoIter = LineIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl PartialEq for Line<'_> {
+    fn eq(&self, other: &Line<'_>) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for Line<'_> {}
+
+impl PartialEq<[Cell]> for Line<'_> {
+    fn eq(&self, other: &[Cell]) -> bool {
+        self.len == other.len() && self.iter().eq(other.iter().copied())
+    }
+}
+
+impl fmt::Debug for Line<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Display for Line<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter().try_for_each(|cell| write!(f, "{}", cell.to_char()))
+    }
+}
+
+impl Grid {
+    /// Returns a view of the `i`th row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    #[must_use]
+    pub fn row(&self, i: usize) -> Line<'_> {
+        assert!(i < self.rows, "row index out of bounds");
+        let edges = self.edges.as_ref().map(|edges| &*edges.right);
+        Line::new(&self.cells, edges, i * self.cols, 1, self.cols)
+    }
+
+    /// Returns a view of the `j`th column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `j` is out of bounds.
+    #[must_use]
+    pub fn col(&self, j: usize) -> Line<'_> {
+        assert!(j < self.cols, "column index out of bounds");
+        let edges = self.edges.as_ref().map(|edges| &*edges.below);
+        Line::new(&self.cells, edges, j, self.cols, self.rows)
+    }
+
+    /// Returns an iterator over views of the rows, from top to bottom.
+    #[must_use]
+    pub fn iter_rows(&self) -> impl ExactSizeIterator<Item = Line<'_>> + '_ {
+        (0..self.rows).map(move |i| self.row(i))
+    }
+
+    /// Returns an iterator over views of the columns, from left to right.
+    #[must_use]
+    pub fn iter_cols(&self) -> impl ExactSizeIterator<Item = Line<'_>> + '_ {
+        (0..self.cols).map(move |j| self.col(j))
+    }
+}
@@ -0,0 +1,263 @@
+use super::{
+    cell::Cell::{self, Empty, One, Zero},
+    edge::Edge,
+};
+
+/// The constraints a line must abide by.
+pub struct LineRules {
+    /// Maximum number of adjacent identical numbers (rule 1).
+    pub max_run: usize,
+    /// Maximum number of either value (rule 2).
+    pub max_count: usize,
+}
+
+/// The state of a line being completed from its start: the last number
+/// placed, the length of the run it ends and the number of `0`s placed.
+#[derive(Clone, Copy)]
+struct State {
+    last: Cell,
+    run: usize,
+    zeros: usize,
+}
+
+/// A line to complete.
+struct Line<'a> {
+    cells: &'a [Cell],
+    edges: &'a [Option<Edge>],
+    rules: &'a LineRules,
+}
+
+impl Line<'_> {
+    /// Returns the number of possible states after placing a cell.
+    const fn states(&self) -> usize {
+        2 * (self.rules.max_run + 1) * (self.cells.len() + 1)
+    }
+
+    /// Returns the index of a state in the tables.
+    fn index(&self, state: State) -> usize {
+        let last = usize::from(state.last == One);
+        (last * (self.rules.max_run + 1) + state.run) * (self.cells.len() + 1) + state.zeros
+    }
+
+    /// Returns the state after placing `value` at position `k` in `state`,
+    /// or `None` if `value` cannot be placed there.
+    fn next(&self, k: usize, state: State, value: Cell) -> Option<State> {
+        if self.cells[k].is_filled() && self.cells[k] != value {
+            return None;
+        }
+        if k > 0 && self.edges[k - 1].is_some_and(|edge| edge.other(state.last) != value) {
+            return None;
+        }
+        let run = if k > 0 && state.last == value { state.run + 1 } else { 1 };
+        let zeros = state.zeros + usize::from(value == Zero);
+        // The backward table also holds unreachable states, with too many `0`s.
+        let ones = (k + 1).checked_sub(zeros)?;
+        let max_count = self.rules.max_count;
+        let legal = run <= self.rules.max_run && zeros <= max_count && ones <= max_count;
+        legal.then_some(State { last: value, run, zeros })
+    }
+
+    /// Returns `true` if `other` is a completion of the line.
+    fn is_completion(&self, other: &[Cell]) -> bool {
+        if other.len() != self.cells.len() || other.contains(&Empty) {
+            return false;
+        }
+        let mut state = Some(State { last: Empty, run: 0, zeros: 0 });
+        for (k, &value) in other.iter().enumerate() {
+            state = state.and_then(|state| self.next(k, state, value));
+        }
+        state.is_some()
+    }
+}
+
+/// Returns, for each cell of a line, whether it can hold a `0` and whether
+/// it can hold a `1` in at least one completion of the line abiding by
+/// the rules and the edge constraints along the line, and different from
+/// all the `excluded` lines.
+///
+/// `edges[k]` is the constraint between the cells `k` and `k + 1`.
+/// All the values are `false` if the line cannot be completed.
+///
+/// The completions are counted rather than enumerated: the number of ways
+/// to complete the line from each state is computed backwards, then the
+/// number of ways to reach each state from the start forwards, which takes
+/// O(`len`² × `max_run`) time. The excluded lines that are completions of
+/// the line are then taken off the counts.
+pub fn candidates(
+    cells: &[Cell],
+    edges: &[Option<Edge>],
+    rules: &LineRules,
+    excluded: &[&[Cell]],
+) -> Vec<[bool; 2]> {
+    let line = Line { cells, edges, rules };
+    let len = cells.len();
+    let values = || IntoIterator::into_iter([Zero, One]);
+    // completions[k][s]: number of ways to complete the line from state `s`
+    // at position `k`. The counts saturate, which can only hide deductions.
+    let mut completions = vec![vec![0_u128; line.states()]; len + 1];
+    completions[len].fill(1);
+    for k in (1..len).rev() {
+        for last in values() {
+            for run in 1..=rules.max_run {
+                for zeros in 0..=len {
+                    let state = State { last, run, zeros };
+                    completions[k][line.index(state)] = values()
+                        .filter_map(|value| line.next(k, state, value))
+                        .fold(0, |count, next| {
+                            count.saturating_add(completions[k + 1][line.index(next)])
+                        });
+                }
+            }
+        }
+    }
+    // counts[k][v]: number of completions with the value `v` at position `k`.
+    let mut counts = vec![[0_u128; 2]; len];
+    let mut reachable = vec![(State { last: Empty, run: 0, zeros: 0 }, 1_u128)];
+    for k in 0..len {
+        let mut ways = vec![0_u128; line.states()];
+        let mut next_reachable = Vec::new();
+        for &(state, count) in &reachable {
+            for value in values() {
+                let Some(next) = line.next(k, state, value) else { continue };
+                let index = line.index(next);
+                let total = count.saturating_mul(completions[k + 1][index]);
+                if total != 0 {
+                    let count_of_value = &mut counts[k][usize::from(value == One)];
+                    *count_of_value = count_of_value.saturating_add(total);
+                    if ways[index] == 0 {
+                        next_reachable.push(next);
+                    }
+                    ways[index] = ways[index].saturating_add(count);
+                }
+            }
+        }
+        reachable = next_reachable.into_iter().map(|next| (next, ways[line.index(next)])).collect();
+    }
+    for (n, other) in excluded.iter().enumerate() {
+        // An excluded line listed twice is only one completion less.
+        if excluded[..n].contains(other) || !line.is_completion(other) {
+            continue;
+        }
+        for (count, &value) in counts.iter_mut().zip(other.iter()) {
+            let count = &mut count[usize::from(value == One)];
+            if *count != u128::MAX {
+                *count -= 1;
+            }
+        }
+    }
+    counts.iter().map(|count| [count[0] != 0, count[1] != 0]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{candidates, LineRules};
+    use crate::{Cell, Edge};
+
+    fn line(s: &str) -> Vec<Cell> {
+        s.chars()
+            .map(|c| match c {
+                '0' => Cell::Zero,
+                '1' => Cell::One,
+                _ => Cell::Empty,
+            })
+            .collect()
+    }
+
+    /// Returns the candidates of a line by enumerating all its completions.
+    fn brute_force(
+        cells: &[Cell],
+        edges: &[Option<Edge>],
+        rules: &LineRules,
+        excluded: &[&[Cell]],
+    ) -> Vec<[bool; 2]> {
+        let len = cells.len();
+        let mut result = vec![[false; 2]; len];
+        for bits in 0..1_u32 << len {
+            let other: Vec<_> =
+                (0..len).map(|k| if bits >> k & 1 == 1 { Cell::One } else { Cell::Zero }).collect();
+            let runs = other.windows(rules.max_run + 1).all(|run| run.iter().any(|&c| c != run[0]));
+            let ones = bits.count_ones() as usize;
+            let completion = runs
+                && ones <= rules.max_count
+                && len - ones <= rules.max_count
+                && cells.iter().zip(&other).all(|(&cell, &value)| cell.is_empty() || cell == value)
+                && edges.iter().enumerate().all(|(k, edge)| {
+                    edge.map_or(true, |edge| edge.other(other[k]) == other[k + 1])
+                })
+                && !excluded.contains(&other.as_slice());
+            if completion {
+                for (values, value) in result.iter_mut().zip(other) {
+                    values[usize::from(value == Cell::One)] = true;
+                }
+            }
+        }
+        result
+    }
+
+    const TAKUZU: LineRules = LineRules { max_run: 2, max_count: 3 };
+    const ANY: [bool; 2] = [true, true];
+    const ZERO: [bool; 2] = [true, false];
+    const ONE: [bool; 2] = [false, true];
+
+    #[test]
+    fn forced_cells() {
+        // Completions: 001011 and 001101.
+        let cells = line("00....");
+        assert_eq!(candidates(&cells, &[None; 5], &TAKUZU, &[]), [ZERO, ZERO, ONE, ANY, ANY, ONE]);
+        assert_eq!(candidates(&line("000..."), &[None; 5], &TAKUZU, &[]), [[false; 2]; 6]);
+        // All the lines of 6 cells.
+        for n in 0..3_usize.pow(6) {
+            let cells: Vec<_> = (0..6)
+                .map(|k| [Cell::Zero, Cell::One, Cell::Empty][n / 3_usize.pow(k) % 3])
+                .collect();
+            let expected = brute_force(&cells, &[None; 5], &TAKUZU, &[]);
+            assert_eq!(candidates(&cells, &[None; 5], &TAKUZU, &[]), expected, "{cells:?}");
+        }
+    }
+
+    #[test]
+    fn edges() {
+        let mut edges = [None; 5];
+        edges[0] = Some(Edge::Equal);
+        edges[3] = Some(Edge::Opposite);
+        // Completions: 001011 and 001101.
+        let cells = line("0.....");
+        assert_eq!(candidates(&cells, &edges, &TAKUZU, &[]), [ZERO, ZERO, ONE, ANY, ANY, ONE]);
+        for cells in ["......", "....1.", ".1....", "...0.."] {
+            let cells = line(cells);
+            let expected = brute_force(&cells, &edges, &TAKUZU, &[]);
+            assert_eq!(candidates(&cells, &edges, &TAKUZU, &[]), expected, "{cells:?}");
+        }
+    }
+
+    #[test]
+    fn excluded_lines() {
+        let rules = LineRules { max_run: 2, max_count: 2 };
+        let cells = line("01..");
+        let excluded = line("0110");
+        let other = line("1001");
+        let expected = [ZERO, ONE, ZERO, ONE];
+        assert_eq!(candidates(&cells, &[None; 3], &rules, &[&excluded, &other]), expected);
+        // Twice the same line is still only one completion less.
+        let twice = [excluded.as_slice(), &excluded];
+        assert_eq!(candidates(&cells, &[None; 3], &rules, &twice), expected);
+        let all = [excluded.as_slice(), &line("0101")];
+        assert_eq!(candidates(&cells, &[None; 3], &rules, &all), [[false; 2]; 4]);
+    }
+
+    #[test]
+    fn saturation() {
+        // Far more than 2¹²⁸ completions: the counts saturate, and excluding
+        // a line does not take them off.
+        let len = 300;
+        let rules = LineRules { max_run: 2, max_count: len / 2 };
+        let mut cells = vec![Cell::Empty; len];
+        cells[0] = Cell::One;
+        cells[1] = Cell::One;
+        let edges = vec![None; len - 1];
+        let excluded = line(&"110".repeat(len / 3));
+        let result = candidates(&cells, &edges, &rules, &[&excluded]);
+        assert_eq!(result[..3], [ONE, ONE, ZERO]);
+        assert!(result[3..].iter().all(|&values| values == ANY));
+    }
+}
@@ -0,0 +1,134 @@
+/// How rule 2 constrains the number of `0`s and `1`s in a line.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Balance {
+    /// Each line contains as many `0`s as `1`s.
+    /// Grids must have an even number of rows and columns.
+    #[default]
+    Equal,
+    /// The numbers of `0`s and `1`s in each line differ by at most one.
+    /// Grids may have an odd number of rows and columns.
+    Relaxed,
+    /// The numbers of `0`s and `1`s are not constrained: rule 2 is disabled.
+    /// Grids may have an odd number of rows and columns.
+    Free,
+}
+
+/// The set of rules a grid must abide by.
+///
+/// The default rule set is the one of the classic takuzu, described in the
+/// [crate documentation](crate). Each rule can be disabled or
+/// parameterised to support the variants of the puzzle:
+///
+/// * rule 1 with [`with_max_run`](RuleSet::with_max_run),
+/// * rule 2 with [`with_balance`](RuleSet::with_balance),
+/// * rule 3 with [`with_unique_lines`](RuleSet::with_unique_lines).
+///
+/// ```
+/// use takuzu::{Balance, RuleSet};
+///
+/// let rules = RuleSet::TAKUZU.with_max_run(Some(3)).with_unique_lines(false);
+/// assert_eq!(rules.balance(), Balance::Equal);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct RuleSet {
+    max_run: Option<usize>,
+    balance: Balance,
+    unique_lines: bool,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::TAKUZU
+    }
+}
+
+impl RuleSet {
+    /// The rules of the classic takuzu.
+    pub const TAKUZU: Self = Self { max_run: Some(2), balance: Balance::Equal, unique_lines: true };
+    /// The rules of the "Tango" variant: the classic rules without rule 3.
+    /// Its grids usually hold [`Edge`](crate::Edge) constraints.
+    pub const TANGO: Self = Self { max_run: Some(2), balance: Balance::Equal, unique_lines: false };
+
+    /// Returns the rules of the variant a grid is written for:
+    /// [`TANGO`](RuleSet::TANGO) if the string holds edge markers,
+    /// [`TAKUZU`](RuleSet::TAKUZU) otherwise.
+    ///
+    /// ```
+    /// use takuzu::RuleSet;
+    ///
+    /// assert_eq!(RuleSet::detect("0=.\n. .\n"), RuleSet::TANGO);
+    /// assert_eq!(RuleSet::detect("0.\n..\n"), RuleSet::TAKUZU);
+    /// ```
+    #[must_use]
+    pub fn detect(s: &str) -> Self {
+        if super::has_edge_markers(s) {
+            Self::TANGO
+        } else {
+            Self::TAKUZU
+        }
+    }
+
+    /// Returns the maximum number of adjacent identical numbers allowed by
+    /// rule 1, or `None` if rule 1 is disabled.
+    #[must_use]
+    pub const fn max_run(self) -> Option<usize> {
+        self.max_run
+    }
+
+    /// Returns the rule set with the given maximum number of adjacent
+    /// identical numbers for rule 1. `None` disables rule 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_run` is `Some(0)`.
+    #[must_use]
+    pub const fn with_max_run(mut self, max_run: Option<usize>) -> Self {
+        assert!(!matches!(max_run, Some(0)), "the maximum run length must be at least 1");
+        self.max_run = max_run;
+        self
+    }
+
+    /// Returns the balance constraint of rule 2.
+    #[must_use]
+    pub const fn balance(self) -> Balance {
+        self.balance
+    }
+
+    /// Returns the rule set with the given balance constraint for rule 2.
+    #[must_use]
+    pub const fn with_balance(mut self, balance: Balance) -> Self {
+        self.balance = balance;
+        self
+    }
+
+    /// Returns `true` if rule 3 is enabled, i.e. if all rows
+    /// and all columns must be different.
+    #[must_use]
+    pub const fn unique_lines(self) -> bool {
+        self.unique_lines
+    }
+
+    /// Returns the rule set with rule 3 enabled or disabled.
+    #[must_use]
+    pub const fn with_unique_lines(mut self, unique_lines: bool) -> Self {
+        self.unique_lines = unique_lines;
+        self
+    }
+
+    /// Returns `true` if the rules allow lines of length `n`.
+    pub(crate) const fn allows_length(self, n: usize) -> bool {
+        match self.balance {
+            Balance::Equal => n % 2 == 0,
+            Balance::Relaxed | Balance::Free => true,
+        }
+    }
+
+    /// Returns the maximum number of either value in a line of length `n`.
+    pub(crate) const fn max_count(self, n: usize) -> usize {
+        match self.balance {
+            Balance::Equal => n / 2,
+            Balance::Relaxed => (n + 1) / 2,
+            Balance::Free => n,
+        }
+    }
+}
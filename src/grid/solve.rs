@@ -0,0 +1,481 @@
+use std::{
+    fmt::{self, Display},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{
+    cell::Cell::{self, One, Zero},
+    error::GridError,
+    tracker::LegalityTracker,
+    Grid,
+};
+
+/// A deduction rule of the solver.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Rule {
+    /// Rule 1: no more than two identical numbers adjacent to each other.
+    Rule1,
+    /// Rule 2: as many `0`s as `1`s in each line.
+    Rule2,
+    /// Rule 3: no two identical lines.
+    Rule3,
+    /// The edge constraints between adjacent cells.
+    Edges,
+    /// The completions of a line abiding by all the rules at once.
+    Lookahead,
+}
+
+impl Rule {
+    /// All the rules, in the order the solver applies them.
+    pub const ALL: [Self; 5] =
+        [Self::Rule1, Self::Edges, Self::Rule2, Self::Rule3, Self::Lookahead];
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Rule1 => write!(f, "rule 1"),
+            Self::Rule2 => write!(f, "rule 2"),
+            Self::Rule3 => write!(f, "rule 3"),
+            Self::Edges => write!(f, "edges"),
+            Self::Lookahead => write!(f, "lookahead"),
+        }
+    }
+}
+
+/// Statistics about a run of the solver.
+///
+/// Returned by [`Grid::solve_with_stats`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct SolveStats {
+    /// Number of grids taken from the stack of the search.
+    pub nodes: usize,
+    /// Number of grids on which the solver had to guess the value of a cell.
+    pub branch_points: usize,
+    /// Maximum number of grids on the stack at the same time.
+    pub max_depth: usize,
+    /// Number of guesses breaking the rules and of filled grids that turned
+    /// out illegal.
+    pub dead_ends: usize,
+    /// Time spent solving.
+    pub elapsed: Duration,
+    /// Number of cells filled by each rule, in the order of [`Rule::ALL`].
+    filled: [usize; 5],
+}
+
+impl SolveStats {
+    /// Returns the number of cells filled by a rule.
+    #[must_use]
+    pub fn filled(&self, rule: Rule) -> usize {
+        self.filled[Self::index(rule)]
+    }
+
+    /// Returns the number of cells filled by the rules, guesses excluded.
+    #[must_use]
+    pub fn filled_total(&self) -> usize {
+        self.filled.iter().sum()
+    }
+
+    /// Returns the index of a rule in [`Rule::ALL`].
+    fn index(rule: Rule) -> usize {
+        Rule::ALL.iter().position(|&other| other == rule).unwrap_or_default()
+    }
+}
+
+impl Display for SolveStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "nodes: {}", self.nodes)?;
+        writeln!(f, "branch points: {}", self.branch_points)?;
+        writeln!(f, "max depth: {}", self.max_depth)?;
+        writeln!(f, "dead ends: {}", self.dead_ends)?;
+        for rule in Rule::ALL {
+            writeln!(f, "filled by {rule}: {}", self.filled(rule))?;
+        }
+        writeln!(f, "time: {:?}", self.elapsed)
+    }
+}
+
+/// Limits on a run of the solver.
+///
+/// By default, the solver runs until all the solutions are found.
+/// When a limit is reached, it stops early and returns the solutions found
+/// so far, see [`Grid::solve_with`].
+///
+/// ```
+/// use std::time::Duration;
+/// use takuzu::{Grid, SolveOptions, StopReason};
+///
+/// let options = SolveOptions::new().with_timeout(Duration::from_secs(1)).with_max_solutions(2);
+/// let outcome = Grid::new(4).unwrap().solve_with(&options).unwrap();
+/// assert_eq!(outcome.solutions.len(), 2);
+/// assert_eq!(outcome.stopped, Some(StopReason::MaxSolutions));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SolveOptions {
+    max_nodes: Option<usize>,
+    max_solutions: Option<usize>,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+impl SolveOptions {
+    /// Returns options without any limit.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { max_nodes: None, max_solutions: None, timeout: None, deadline: None, cancel: None }
+    }
+
+    /// Stops the search after exploring `max_nodes` grids
+    /// (see [`SolveStats::nodes`]).
+    #[must_use]
+    pub const fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    /// Stops the search once `max_solutions` solutions are found.
+    /// 0 means no limit.
+    ///
+    /// Use 2 to tell a valid grid, with a single solution, from the others.
+    #[must_use]
+    pub const fn with_max_solutions(mut self, max_solutions: usize) -> Self {
+        self.max_solutions = if max_solutions == 0 { None } else { Some(max_solutions) };
+        self
+    }
+
+    /// Stops the search once it has run for `timeout`.
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Stops the search once `deadline` is reached.
+    #[must_use]
+    pub const fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Stops the search once `cancel` is set to `true`, e.g. from another
+    /// thread.
+    #[must_use]
+    pub fn with_cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Returns the reason to stop the search, if any.
+    fn check(&self, deadline: Option<Instant>, stats: &SolveStats) -> Option<StopReason> {
+        if self.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+            Some(StopReason::Cancelled)
+        } else if self.max_nodes.is_some_and(|max_nodes| stats.nodes >= max_nodes) {
+            Some(StopReason::MaxNodes)
+        } else if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Some(StopReason::Timeout)
+        } else {
+            None
+        }
+    }
+}
+
+/// Why the solver stopped before exploring all the grids.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum StopReason {
+    /// The maximum number of nodes was reached.
+    MaxNodes,
+    /// The maximum number of solutions was found.
+    MaxSolutions,
+    /// The timeout or the deadline was reached.
+    Timeout,
+    /// The search was cancelled.
+    Cancelled,
+}
+
+impl Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::MaxNodes => write!(f, "maximum number of nodes reached"),
+            Self::MaxSolutions => write!(f, "maximum number of solutions found"),
+            Self::Timeout => write!(f, "timeout reached"),
+            Self::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// The result of a run of the solver with [`Grid::solve_with`].
+#[derive(Clone, Debug)]
+pub struct SolveOutcome {
+    /// The solutions found, in the order of [`Grid::solve`].
+    pub solutions: Vec<Grid>,
+    /// Statistics about the search.
+    pub stats: SolveStats,
+    /// Why the search stopped early, or `None` if it was complete,
+    /// in which case all the solutions were found.
+    pub stopped: Option<StopReason>,
+}
+
+impl SolveOutcome {
+    /// Returns `true` if the search was complete,
+    /// i.e. if all the solutions were found.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.stopped.is_none()
+    }
+}
+
+/// Callbacks to follow the search of the solver as it runs,
+/// see [`Grid::solve_observed`].
+///
+/// The search is depth-first: it takes grids from a stack, fills them with
+/// the rules, then either guesses the value of a cell, pushing both choices,
+/// or checks the filled grid. All the methods do nothing by default.
+///
+/// ```
+/// use takuzu::{Grid, SolveObserver, SolveOptions};
+///
+/// #[derive(Default)]
+/// struct Counts {
+///     guesses: usize,
+///     solutions: usize,
+/// }
+///
+/// impl SolveObserver for Counts {
+///     fn on_guess(&mut self, _: &Grid, _: (usize, usize)) {
+///         self.guesses += 1;
+///     }
+///     fn on_solution(&mut self, _: &Grid) {
+///         self.solutions += 1;
+///     }
+/// }
+///
+/// let mut counts = Counts::default();
+/// let grid = Grid::new(4).unwrap();
+/// let outcome = grid.solve_observed(&SolveOptions::new(), &mut counts).unwrap();
+/// assert_eq!(counts.solutions, outcome.solutions.len());
+/// ```
+pub trait SolveObserver {
+    /// Called when a rule fills the cell at `coord` with `value`.
+    fn on_assign(&mut self, coord: (usize, usize), value: Cell, rule: Rule) {
+        let _ = (coord, value, rule);
+    }
+
+    /// Called when the search goes on from a guess: `grid` is the grid taken
+    /// from the stack, with the guessed value at `coord`.
+    ///
+    /// The grid can differ from the previous one in any number of cells
+    /// when the search resumes after a backtrack or a solution.
+    fn on_guess(&mut self, grid: &Grid, coord: (usize, usize)) {
+        let _ = (grid, coord);
+    }
+
+    /// Called when the search gives up on a grid breaking the rules,
+    /// to resume from the next grid on the stack.
+    fn on_backtrack(&mut self, grid: &Grid) {
+        let _ = grid;
+    }
+
+    /// Called when a solution is found.
+    fn on_solution(&mut self, solution: &Grid) {
+        let _ = solution;
+    }
+}
+
+/// An observer ignoring the search.
+struct Unobserved;
+
+/// Number of levels of guesses split across threads by
+/// [`Grid::par_solve`]. Deeper branches are searched sequentially.
+#[cfg(feature = "parallel")]
+const SPLIT_DEPTH: usize = 12;
+
+impl SolveObserver for Unobserved {}
+
+impl Grid {
+    /// Solves the grid using both rules logic and a backtracking algorithm.
+    ///
+    /// Returns an array containing the solution(s), or an empty array if there
+    /// are none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error before any attempt at solving if
+    /// the grid breaks any of the rules
+    /// (i.e. if [`is_legal`](Grid::is_legal) returns `false`).
+    pub fn solve(&self) -> Result<Vec<Self>, GridError> {
+        self.solve_with_stats().map(|(solutions, _)| solutions)
+    }
+
+    /// Same as [`solve`](Grid::solve), also returning statistics about the
+    /// search.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error before any attempt at solving if
+    /// the grid breaks any of the rules
+    /// (i.e. if [`is_legal`](Grid::is_legal) returns `false`).
+    pub fn solve_with_stats(&self) -> Result<(Vec<Self>, SolveStats), GridError> {
+        let outcome = self.solve_with(&SolveOptions::new())?;
+        Ok((outcome.solutions, outcome.stats))
+    }
+
+    /// Same as [`solve`](Grid::solve), stopping early if one of the limits
+    /// of `options` is reached.
+    ///
+    /// The solutions found are returned in any case, with statistics about
+    /// the search and the reason why it stopped early, if it did.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error before any attempt at solving if
+    /// the grid breaks any of the rules
+    /// (i.e. if [`is_legal`](Grid::is_legal) returns `false`).
+    pub fn solve_with(&self, options: &SolveOptions) -> Result<SolveOutcome, GridError> {
+        self.solve_observed(options, &mut Unobserved)
+    }
+
+    /// Same as [`solve_with`](Grid::solve_with), reporting the progress of
+    /// the search to `observer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error before any attempt at solving if
+    /// the grid breaks any of the rules
+    /// (i.e. if [`is_legal`](Grid::is_legal) returns `false`).
+    pub fn solve_observed(
+        &self,
+        options: &SolveOptions,
+        observer: &mut impl SolveObserver,
+    ) -> Result<SolveOutcome, GridError> {
+        if !self.is_legal() {
+            return Err(GridError::Illegal);
+        }
+        let start = Instant::now();
+        let deadline = match (options.timeout.map(|timeout| start + timeout), options.deadline) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        let mut stats = SolveStats::default();
+        let (mut stack, mut solutions) = (Vec::new(), Vec::new());
+        let mut stopped = None;
+        // Each grid comes with the coordinates of the guess it was pushed for.
+        stack.push((LegalityTracker::new(self.clone()), None));
+        stats.max_depth = 1;
+        while !stack.is_empty() {
+            stopped = options.check(deadline, &stats);
+            if stopped.is_some() {
+                break;
+            }
+            let Some((mut tracker, guess)) = stack.pop() else { break };
+            stats.nodes += 1;
+            if let Some(coord) = guess {
+                observer.on_guess(tracker.grid(), coord);
+                if !tracker.is_cell_legal(coord) {
+                    stats.dead_ends += 1;
+                    observer.on_backtrack(tracker.grid());
+                    continue;
+                }
+            }
+            propagate(&mut tracker, &mut stats, observer);
+            match tracker.grid().next_empty() {
+                Some(coord) => {
+                    stats.branch_points += 1;
+                    let mut one = tracker.clone();
+                    one.set(coord, One);
+                    stack.push((one, Some(coord)));
+                    tracker.set(coord, Zero);
+                    stack.push((tracker, Some(coord)));
+                    stats.max_depth = stats.max_depth.max(stack.len());
+                }
+                None => {
+                    if tracker.is_legal() {
+                        let grid = tracker.into_grid();
+                        observer.on_solution(&grid);
+                        solutions.push(grid);
+                        if !stack.is_empty()
+                            && options.max_solutions.is_some_and(|max| solutions.len() >= max)
+                        {
+                            stopped = Some(StopReason::MaxSolutions);
+                            break;
+                        }
+                    } else {
+                        stats.dead_ends += 1;
+                        observer.on_backtrack(tracker.grid());
+                    }
+                }
+            }
+        }
+        stats.elapsed = start.elapsed();
+        Ok(SolveOutcome { solutions, stats, stopped })
+    }
+
+    /// Same as [`solve`](Grid::solve), searching the branches of the first
+    /// guesses in parallel on the global `rayon` thread pool.
+    ///
+    /// The solutions come out in the same order as with `solve`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error before any attempt at solving if
+    /// the grid breaks any of the rules
+    /// (i.e. if [`is_legal`](Grid::is_legal) returns `false`).
+    #[cfg(feature = "parallel")]
+    pub fn par_solve(&self) -> Result<Vec<Self>, GridError> {
+        if !self.is_legal() {
+            return Err(GridError::Illegal);
+        }
+        Ok(par_search(LegalityTracker::new(self.clone()), SPLIT_DEPTH))
+    }
+}
+
+/// Searches the solutions of a grid, splitting the branches of the next
+/// `depth` guesses between threads, the `0` branch first.
+#[cfg(feature = "parallel")]
+fn par_search(mut tracker: LegalityTracker, depth: usize) -> Vec<Grid> {
+    if depth == 0 {
+        // An illegal grid has no solution.
+        return tracker.into_grid().solve().unwrap_or_default();
+    }
+    propagate(&mut tracker, &mut SolveStats::default(), &mut Unobserved);
+    let Some(coord) = tracker.grid().next_empty() else {
+        return if tracker.is_legal() { vec![tracker.into_grid()] } else { Vec::new() };
+    };
+    let branch = |value| {
+        let mut tracker = tracker.clone();
+        tracker.set(coord, value);
+        if tracker.is_cell_legal(coord) {
+            par_search(tracker, depth - 1)
+        } else {
+            Vec::new()
+        }
+    };
+    let (mut solutions, ones) = rayon::join(|| branch(Zero), || branch(One));
+    solutions.extend(ones);
+    solutions
+}
+
+/// Applies the rules to the tracked grid until none of them fills any more
+/// cell, counting the cells filled by each rule and reporting them to
+/// `observer`.
+fn propagate(
+    tracker: &mut LegalityTracker,
+    stats: &mut SolveStats,
+    observer: &mut impl SolveObserver,
+) {
+    let mut grid = tracker.grid().clone();
+    while let Some(rule) = grid.apply_rules() {
+        for (index, &new) in grid.cells.iter().enumerate() {
+            if tracker.grid().cells[index] != new {
+                let coord = (index / grid.cols, index % grid.cols);
+                tracker.set(coord, new);
+                stats.filled[SolveStats::index(rule)] += 1;
+                observer.on_assign(coord, new, rule);
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use super::{
+    cell::Cell::{self, Empty},
+    edge::Edges,
+    Grid,
+};
+
+impl Grid {
+    /// Returns a grid of `rows` × `cols` holding the cells and the edge
+    /// constraints of the grid moved from `(i, j)` to `map((i, j))`.
+    ///
+    /// `map` must be a bijection that keeps adjacent cells adjacent.
+    fn remap(
+        &self,
+        (rows, cols): (usize, usize),
+        map: impl Fn((usize, usize)) -> (usize, usize),
+    ) -> Self {
+        let mut image = Self::from_parts(vec![Empty; rows * cols], rows, cols, self.rules);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                image[map((i, j))] = self[(i, j)];
+            }
+        }
+        if self.edges.is_some() {
+            let mut set_edge = |a, b, edge| {
+                let (a, b) = (map(a), map(b));
+                if a.0 == b.0 {
+                    image.set_edge_right(a.min(b), edge);
+                } else {
+                    image.set_edge_below(a.min(b), edge);
+                }
+            };
+            for i in 0..self.rows {
+                for j in 0..self.cols {
+                    if j + 1 < self.cols {
+                        set_edge((i, j), (i, j + 1), self.edge_right((i, j)));
+                    }
+                    if i + 1 < self.rows {
+                        set_edge((i, j), (i + 1, j), self.edge_below((i, j)));
+                    }
+                }
+            }
+        }
+        image
+    }
+
+    /// Returns the transpose of the grid, whose rows are the columns
+    /// of the grid.
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        self.remap((self.cols, self.rows), |(i, j)| (j, i))
+    }
+
+    /// Returns the grid rotated by a quarter turn clockwise.
+    #[must_use]
+    pub fn rotate90(&self) -> Self {
+        let rows = self.rows;
+        self.remap((self.cols, rows), |(i, j)| (j, rows - 1 - i))
+    }
+
+    /// Returns the grid rotated by a half turn.
+    #[must_use]
+    pub fn rotate180(&self) -> Self {
+        let (rows, cols) = (self.rows, self.cols);
+        self.remap((rows, cols), |(i, j)| (rows - 1 - i, cols - 1 - j))
+    }
+
+    /// Returns the grid rotated by a quarter turn counterclockwise.
+    #[must_use]
+    pub fn rotate270(&self) -> Self {
+        let cols = self.cols;
+        self.remap((cols, self.rows), |(i, j)| (cols - 1 - j, i))
+    }
+
+    /// Returns the mirror image of the grid, with the cells of each row
+    /// in reverse order.
+    #[must_use]
+    pub fn flip_horizontal(&self) -> Self {
+        let cols = self.cols;
+        self.remap((self.rows, cols), |(i, j)| (i, cols - 1 - j))
+    }
+
+    /// Returns the grid upside down, with its rows in reverse order.
+    #[must_use]
+    pub fn flip_vertical(&self) -> Self {
+        let rows = self.rows;
+        self.remap((rows, self.cols), |(i, j)| (rows - 1 - i, j))
+    }
+
+    /// Returns the grid with its `0`s and `1`s swapped.
+    ///
+    /// The edge constraints are unchanged.
+    #[must_use]
+    pub fn invert(&self) -> Self {
+        let mut inverse = self.clone();
+        for cell in &mut inverse.cells {
+            *cell = !*cell;
+        }
+        inverse
+    }
+
+    /// Returns the grid with its rows reordered: row `k` of the new grid
+    /// is row `order[k]` of the grid.
+    ///
+    /// Returns `None` if the new order would change the solutions of the
+    /// grid, i.e. if the grid has a maximum run length or edge constraints
+    /// and the order separates adjacent rows. Otherwise the rules only
+    /// depend on the contents of the lines, not on their order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is not a permutation of the rows.
+    #[must_use]
+    pub fn permute_rows(&self, order: &[usize]) -> Option<Self> {
+        self.transpose().permute_cols(order).map(|grid| grid.transpose())
+    }
+
+    /// Returns the grid with its columns reordered: column `k` of the new
+    /// grid is column `order[k]` of the grid.
+    ///
+    /// Returns `None` if the new order would change the solutions of the
+    /// grid, as for [`permute_rows`](Grid::permute_rows).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is not a permutation of the columns.
+    #[must_use]
+    pub fn permute_cols(&self, order: &[usize]) -> Option<Self> {
+        let mut seen = vec![false; self.cols];
+        for &j in order {
+            assert!(j < self.cols && !seen[j], "not a permutation of the columns");
+            seen[j] = true;
+        }
+        assert!(order.len() == self.cols, "not a permutation of the columns");
+        // Only the identity and the reversal keep adjacent columns adjacent.
+        if order.windows(2).all(|pair| pair[0].abs_diff(pair[1]) == 1) {
+            let reversed = order.first() > order.last();
+            return Some(if reversed { self.flip_horizontal() } else { self.clone() });
+        }
+        if self.rules.max_run().is_some() || self.edges.is_some() {
+            return None;
+        }
+        let mut image = self.clone();
+        for (k, &j) in order.iter().enumerate() {
+            for i in 0..self.rows {
+                image[(i, k)] = self[(i, j)];
+            }
+        }
+        Some(image)
+    }
+
+    /// Returns the lexicographically smallest grid equivalent to this one
+    /// by a rotation, a reflection and the swapping of `0`s and `1`s.
+    ///
+    /// Grids are compared by their dimensions, then their cells row by row,
+    /// `0` < `1` < `.`, then their edge constraints. Two grids are the same
+    /// puzzle up to symmetry if and only if their canonical forms are equal,
+    /// row and column permutations aside.
+    ///
+    /// ```
+    /// use takuzu::Grid;
+    ///
+    /// let grid = "1.\n..\n".parse::<Grid>().unwrap();
+    /// let other = "..\n.0\n".parse::<Grid>().unwrap();
+    /// assert_eq!(grid.canonical(), other.canonical());
+    /// assert_eq!(grid.canonical().to_string(), "0.\n..\n");
+    /// ```
+    #[must_use]
+    pub fn canonical(&self) -> Self {
+        let images = IntoIterator::into_iter([self.clone(), self.transpose()]).flat_map(|grid| {
+            [grid.flip_horizontal(), grid.flip_vertical(), grid.rotate180(), grid]
+        });
+        let mut canonical = self.clone();
+        for image in images.flat_map(|image| [image.invert(), image]) {
+            if image.order_key() < canonical.order_key() {
+                canonical = image;
+            }
+        }
+        canonical
+    }
+
+    /// Returns the key grids are ordered by in [`canonical`](Grid::canonical).
+    fn order_key(&self) -> (usize, usize, &[Cell], Option<&Edges>) {
+        (self.rows, self.cols, &self.cells, self.edges.as_deref())
+    }
+}
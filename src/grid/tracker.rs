@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use super::{
+    cell::Cell::{self, Empty, One, Zero},
+    Grid,
+};
+
+/// A grid that keeps track of its legality as its cells change.
+///
+/// [`Grid::is_legal`] and [`Grid::is_cell_legal`] scan whole lines, and
+/// the whole grid for rule 3, every time they are called. The tracker
+/// instead maintains the counts of `0`s and `1`s of every line, the number
+/// of runs breaking rule 1, the completed lines and the broken edge
+/// constraints. [`set`](LegalityTracker::set) updates them in O(n) time
+/// at most, and [`is_legal`](LegalityTracker::is_legal) answers in O(1).
+///
+/// It serves both the solver, where each branch of the search carries its
+/// own tracker, and interactive play in a [`PlaySession`](crate::PlaySession).
+///
+/// ```
+/// use takuzu::{Cell, Grid, LegalityTracker};
+///
+/// let mut tracker = LegalityTracker::new("1...\n....\n....\n....\n".parse::<Grid>().unwrap());
+/// tracker.set((0, 1), Cell::One);
+/// assert!(tracker.is_legal());
+/// tracker.set((0, 2), Cell::One);
+/// assert!(!tracker.is_legal() && !tracker.is_cell_legal((0, 1)));
+/// ```
+#[derive(Clone, Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct LegalityTracker {
+    grid: Grid,
+    /// Counts of `0`s and `1`s of each row, then of each column.
+    counts: Box<[(usize, usize)]>,
+    /// Number of runs of identical numbers longer than allowed by rule 1.
+    long_runs: usize,
+    /// Number of lines with too many `0`s or `1`s.
+    unbalanced_lines: usize,
+    /// Number of completed lines identical to another one.
+    duplicate_lines: usize,
+    /// Number of broken edge constraints.
+    broken_edges: usize,
+    /// Completed rows, with the number of times each appears.
+    rows: HashMap<Box<[Cell]>, usize>,
+    /// Completed columns, with the number of times each appears.
+    cols: HashMap<Box<[Cell]>, usize>,
+}
+
+impl LegalityTracker {
+    /// Starts tracking the legality of a grid.
+    #[must_use]
+    pub fn new(mut grid: Grid) -> Self {
+        let empty = vec![Empty; grid.cells.len()].into_boxed_slice();
+        let cells = std::mem::replace(&mut grid.cells, empty);
+        let (rows, cols) = grid.dimensions();
+        let mut tracker = Self {
+            grid,
+            counts: vec![(0, 0); rows + cols].into_boxed_slice(),
+            long_runs: 0,
+            unbalanced_lines: 0,
+            duplicate_lines: 0,
+            broken_edges: 0,
+            rows: HashMap::new(),
+            cols: HashMap::new(),
+        };
+        for (index, &cell) in cells.iter().enumerate() {
+            tracker.set((index / cols, index % cols), cell);
+        }
+        tracker
+    }
+
+    /// Returns the tracked grid.
+    #[must_use]
+    pub const fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Stops tracking and returns the grid.
+    #[must_use]
+    pub fn into_grid(self) -> Grid {
+        self.grid
+    }
+
+    /// Sets the value of the cell at `coord`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn set(&mut self, coord: (usize, usize), cell: Cell) {
+        let (i, j) = coord;
+        assert!(i < self.grid.rows && j < self.grid.cols, "cell coordinates out of bounds");
+        if self.grid[coord] == cell {
+            return;
+        }
+        self.update(coord, false);
+        self.grid[coord] = cell;
+        self.update(coord, true);
+    }
+
+    /// Returns `true` if the grid does not currently violate any of the rules,
+    /// like [`Grid::is_legal`].
+    #[must_use]
+    pub const fn is_legal(&self) -> bool {
+        self.long_runs == 0
+            && self.unbalanced_lines == 0
+            && self.duplicate_lines == 0
+            && self.broken_edges == 0
+    }
+
+    /// Returns `true` if a certain cell does not violate any of the rules,
+    /// like [`Grid::is_cell_legal`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    #[must_use]
+    pub fn is_cell_legal(&self, coord: (usize, usize)) -> bool {
+        let (i, j) = coord;
+        assert!(i < self.grid.rows && j < self.grid.cols, "cell coordinates out of bounds");
+        let grid = &self.grid;
+        let duplicate = |lines: &HashMap<Box<[Cell]>, usize>, line: Box<[Cell]>| {
+            lines.get(&line).is_some_and(|&count| count > 1)
+        };
+        grid[coord].is_empty() || {
+            self.long_runs_at(coord) == 0
+                && !self.is_unbalanced(i)
+                && !self.is_unbalanced(grid.rows + j)
+                && !self.row(i).is_some_and(|row| duplicate(&self.rows, row))
+                && !self.col(j).is_some_and(|col| duplicate(&self.cols, col))
+                && grid.check_cell_edges(coord)
+        }
+    }
+
+    /// Adds (if `add` is `true`) or removes the contributions of the cell
+    /// at `coord` and of its lines to the counters.
+    fn update(&mut self, coord: (usize, usize), add: bool) {
+        let (i, j) = coord;
+        let lines = [i, self.grid.rows + j];
+        if add {
+            self.count(lines, add);
+        }
+        let cell = self.grid[coord];
+        let long_runs = self.long_runs_at(coord);
+        let broken_edges = self
+            .grid
+            .neighbours(coord)
+            .filter(|&(other, edge)| !edge.holds(cell, self.grid[other]));
+        let broken_edges = broken_edges.count();
+        let unbalanced = lines.iter().filter(|&&line| self.is_unbalanced(line)).count();
+        let (row, col) = (self.row(i), self.col(j));
+        if add {
+            self.long_runs += long_runs;
+            self.broken_edges += broken_edges;
+            self.unbalanced_lines += unbalanced;
+            if let Some(row) = row {
+                self.duplicate_lines += usize::from(add_line(&mut self.rows, row));
+            }
+            if let Some(col) = col {
+                self.duplicate_lines += usize::from(add_line(&mut self.cols, col));
+            }
+        } else {
+            self.long_runs -= long_runs;
+            self.broken_edges -= broken_edges;
+            self.unbalanced_lines -= unbalanced;
+            if let Some(row) = row {
+                self.duplicate_lines -= usize::from(remove_line(&mut self.rows, &row));
+            }
+            if let Some(col) = col {
+                self.duplicate_lines -= usize::from(remove_line(&mut self.cols, &col));
+            }
+            self.count(lines, add);
+        }
+    }
+
+    /// Adds (if `add` is `true`) or removes the cell at the crossing
+    /// of `lines` to their counts of `0`s and `1`s.
+    fn count(&mut self, lines: [usize; 2], add: bool) {
+        let cell = self.grid[(lines[0], lines[1] - self.grid.rows)];
+        for line in IntoIterator::into_iter(lines) {
+            let count = &mut self.counts[line];
+            let count = match cell {
+                Zero => &mut count.0,
+                One => &mut count.1,
+                Empty => continue,
+            };
+            if add {
+                *count += 1;
+            } else {
+                *count -= 1;
+            }
+        }
+    }
+
+    /// Returns the number of runs longer than allowed by rule 1
+    /// that the cell at `coord` belongs to.
+    fn long_runs_at(&self, (row, col): (usize, usize)) -> usize {
+        use std::cmp::min;
+
+        let grid = &self.grid;
+        let cell = grid[(row, col)];
+        let Some(max_run) = grid.rules.max_run() else { return 0 };
+        if cell.is_empty() {
+            return 0;
+        }
+        let rows = row.saturating_sub(max_run)..min(row + 1, grid.rows.saturating_sub(max_run));
+        let cols = col.saturating_sub(max_run)..min(col + 1, grid.cols.saturating_sub(max_run));
+        let vertical = rows.filter(|&i| (0..=max_run).all(|k| grid[(i + k, col)] == cell));
+        let horizontal = cols.filter(|&j| (0..=max_run).all(|k| grid[(row, j + k)] == cell));
+        vertical.count() + horizontal.count()
+    }
+
+    /// Returns `true` if the `line`th line, rows first then columns,
+    /// has too many `0`s or `1`s.
+    fn is_unbalanced(&self, line: usize) -> bool {
+        let len = if line < self.grid.rows { self.grid.cols } else { self.grid.rows };
+        let nmax = self.grid.rules.max_count(len);
+        let (zeros, ones) = self.counts[line];
+        zeros > nmax || ones > nmax
+    }
+
+    /// Returns the `i`th row if it is complete and rule 3 is enabled.
+    fn row(&self, i: usize) -> Option<Box<[Cell]>> {
+        let (zeros, ones) = self.counts[i];
+        (self.grid.rules.unique_lines() && zeros + ones == self.grid.cols)
+            .then(|| self.grid.row(i).iter().collect())
+    }
+
+    /// Returns the `j`th column if it is complete and rule 3 is enabled.
+    fn col(&self, j: usize) -> Option<Box<[Cell]>> {
+        let (zeros, ones) = self.counts[self.grid.rows + j];
+        (self.grid.rules.unique_lines() && zeros + ones == self.grid.rows)
+            .then(|| self.grid.col(j).iter().collect())
+    }
+}
+
+/// Adds a completed line. Returns `true` if it was already there.
+fn add_line(lines: &mut HashMap<Box<[Cell]>, usize>, line: Box<[Cell]>) -> bool {
+    let count = lines.entry(line).or_insert(0);
+    *count += 1;
+    *count > 1
+}
+
+/// Removes a completed line. Returns `true` if it is still there.
+fn remove_line(lines: &mut HashMap<Box<[Cell]>, usize>, line: &[Cell]) -> bool {
+    let count = lines.get_mut(line).expect("completed lines are tracked");
+    *count -= 1;
+    if *count == 0 {
+        lines.remove(line);
+        return false;
+    }
+    true
+}
@@ -0,0 +1,98 @@
+use super::{
+    error::{GridError, GridParseError},
+    Grid,
+};
+
+/// The differences between the solutions of a grid and the expected ones.
+///
+/// Returned by [`Grid::verify`]. The order of the solutions is ignored.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Verification {
+    /// The expected solutions the solver did not find.
+    pub missing: Vec<Grid>,
+    /// The solutions found by the solver that were not expected.
+    pub extra: Vec<Grid>,
+}
+
+impl Verification {
+    /// Compares solutions with the expected ones, ignoring their order.
+    ///
+    /// A solution found twice but expected once is an extra solution.
+    #[must_use]
+    pub fn new(solutions: &[Grid], expected: &[Grid]) -> Self {
+        let mut found = vec![false; expected.len()];
+        let mut extra = Vec::new();
+        for solution in solutions {
+            let index =
+                expected.iter().zip(&found).position(|(grid, &found)| !found && grid == solution);
+            match index {
+                Some(index) => found[index] = true,
+                None => extra.push(solution.clone()),
+            }
+        }
+        let missing = expected.iter().zip(found).filter(|&(_, found)| !found);
+        let missing = missing.map(|(grid, _)| grid.clone()).collect();
+        Self { missing, extra }
+    }
+
+    /// Returns `true` if the solutions are exactly the expected ones.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+impl Grid {
+    /// Parses a list of solutions of the grid, in the format of
+    /// [`Display`](std::fmt::Display): the grids separated by empty lines,
+    /// as in the output files of the example grids. Extra empty lines
+    /// between the solutions and at the end are ignored.
+    ///
+    /// Each solution spans as many lines as the grid, so that grids with
+    /// edge constraints, which can have empty lines, are split right.
+    /// The solutions follow the rules of the grid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the solutions is not a valid grid.
+    pub fn parse_solutions(&self, s: &str) -> Result<Vec<Self>, GridParseError> {
+        let height = self.to_string().lines().count();
+        let lines: Vec<_> = s.lines().collect();
+        let mut solutions = Vec::new();
+        let mut start = 0;
+        loop {
+            // Skip the empty lines separating the solutions, and those at
+            // the end. A solution never starts with an empty line.
+            while lines.get(start).is_some_and(|line| line.is_empty()) {
+                start += 1;
+            }
+            if start == lines.len() {
+                return Ok(solutions);
+            }
+            let end = (start + height).min(lines.len());
+            let solution = lines[start..end].iter().fold(String::new(), |s, line| s + line + "\n");
+            solutions.push(Self::parse_with_rules(&solution, self.rules)?);
+            start = end;
+        }
+    }
+
+    /// Solves the grid and compares its solutions with the expected ones,
+    /// ignoring their order.
+    ///
+    /// ```
+    /// use takuzu::Grid;
+    ///
+    /// let grid = "1.\n..\n".parse::<Grid>().unwrap();
+    /// let expected = grid.parse_solutions("10\n01\n").unwrap();
+    /// assert!(grid.verify(&expected).unwrap().is_ok());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error before any attempt at solving if
+    /// the grid breaks any of the rules
+    /// (i.e. if [`is_legal`](Grid::is_legal) returns `false`).
+    pub fn verify(&self, expected: &[Self]) -> Result<Verification, GridError> {
+        Ok(Verification::new(&self.solve()?, expected))
+    }
+}
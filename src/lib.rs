@@ -17,7 +17,13 @@
 //! * each row and each column must contain an equal number of `0`s and `1`s.
 //! * no two rows and no two columns are the same.
 //!
-//! The grids are squares of even size.
+//! The grids are rectangles with an even number of rows and columns,
+//! usually squares.
+//!
+//! Variants of the puzzle are supported through [`RuleSet`]: each rule can
+//! be disabled or parameterised, e.g. to allow longer runs of adjacent
+//! numbers or grids of odd sizes, where the numbers of `0`s and `1`s
+//! in a line differ by one ([`Balance::Relaxed`]).
 //! A valid grid must have one and only one solution.
 //! The solver will find and return all valid solutions though.
 //!
@@ -27,14 +33,43 @@
 //! `0`, `1`, `.` for a missing number, and one `\n` at the end of each row.
 //! The final `\n` may be omitted.
 //!
+//! Grids with [`Edge`] constraints between adjacent cells (the "Tango"
+//! variant) use an extended format: the cells of a row are separated by a
+//! space or an edge marker, `=` for [`Edge::Equal`] and `x` or `×` for
+//! [`Edge::Opposite`], and each row is followed by a line holding the
+//! markers of the edges below its cells, aligned with them.
+//! A grid is read in this format if it holds at least one edge marker,
+//! and then follows [`RuleSet::TANGO`] unless parsed with other rules.
+//!
+//! ```text
+//! 0=. . .
+//!     x
+//! . . .x1
+//! ```
+//!
 //! [Example grids](https://github.com/letheed/takuzu/tree/master/grids)
+//!
+//! # Features
+//!
+//! * `parallel`: adds `Grid::par_solve`, which searches the branches of
+//!   the solver on several threads with `rayon`.
 
-pub use ansi::AnsiGridDiff;
+pub use ansi::{AnsiGridDiff, Color, PaintedGrid, Theme, ThemedGridDiff};
 pub use grid::{
     cell::Cell,
+    edge::Edge,
     error::{GridError, GridParseError, GridSizeError},
+    line::{Line, LineIter},
+    rules::{Balance, RuleSet},
+    solve::{Rule, SolveObserver, SolveOptions, SolveOutcome, SolveStats, StopReason},
+    tracker::LegalityTracker,
+    verify::Verification,
     Grid,
 };
+pub use play::{log::MoveLog, Marks, Move, PlayError, PlaySession};
+pub use pretty::PrettyGrid;
 
 mod ansi;
 mod grid;
+mod play;
+mod pretty;
@@ -4,63 +4,842 @@
 #![warn(clippy::nursery)]
 
 use std::{
-    fmt::{self, Display},
-    io::Read,
+    collections::BTreeMap,
+    fmt::{self, Display, Write as _},
+    io::{Read, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-use anyhow::Error;
-use takuzu::{AnsiGridDiff, Grid};
+use anyhow::{Context, Error};
+use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
+use takuzu::{
+    AnsiGridDiff, Balance, Cell, Color, Grid, GridError, GridParseError, PaintedGrid, Rule,
+    RuleSet, SolveObserver, SolveOptions, SolveOutcome, Theme,
+};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const USAGE_STRING: &str = "\
-Usage: takuzu [FILE]...
+Usage: takuzu [solve] [OPTION]... [FILE | -r DIR]...
+       takuzu verify [OPTION]... GRID EXPECTED
        takuzu {--help | --version}
 
-If no FILE is provided, or if FILE is '-', read from standard input.
+'takuzu solve' solves the grids in the files, the default if no command is
+given. If no FILE or DIR is provided, or if FILE is '-', read from standard
+input.
+
+'takuzu verify' solves GRID and compares its solutions with those listed in
+EXPECTED, in any order, then shows the missing and extra solutions. It exits
+with status 1 if they differ.
+
+Exit status is 0 if all the files were solved, 1 if a file could not be read,
+is not a valid grid, breaks the rules or has no solution, and 2 if the
+command line is invalid.
+
+The argument of an option may follow it after '=' or as the next argument,
+e.g. '--delay=20' or '--delay 20'.
 
 Options:
-    --help       display this message and exit
-    --version    display the version and exit
+    --color=WHEN    colorize the output; WHEN is 'auto' (default),
+                    'always' or 'never'
+    --theme=NAME    color theme; NAME is 'default', 'colorblind',
+                    '256' or 'truecolor'
+    --pretty        draw the grids with box-drawing characters
+    --indices       with --pretty, number the rows and columns
+    --blocks=N      with --pretty, draw a thicker line every N cells
+    --max-run=N     allow at most N adjacent identical numbers (default 2),
+                    or any number if N is 'none'
+    --balance=MODE  numbers of 0s and 1s in a line; MODE is 'equal' (default),
+                    'relaxed' (may differ by one) or 'free'
+    --duplicates    allow identical rows and identical columns
+    --tango         use the rules of the Tango variant, which the grids with
+                    edge constraints follow by default
+    --stats         print statistics about the search to standard error
+    --max-solutions=N
+                    stop the search after finding N solutions
+    --timeout=SECS  stop the search after SECS seconds (may be fractional)
+    --animate       draw the search on standard error as it runs, with
+                    a color for each rule and a flash on each backtrack
+    --delay=MS      with --animate, wait MS milliseconds between steps
+                    (default 50)
+    --jobs=N        solve N files at a time, printing the results in the
+                    order of the files, then a summary to standard error
+    -r DIR, --recursive=DIR
+                    solve the files in DIR and its subdirectories
+    --include=GLOB  with -r, only solve the files whose path in DIR matches
+                    GLOB (may be repeated)
+    --exclude=GLOB  with -r, do not solve the files whose path in DIR
+                    matches GLOB (may be repeated)
+    --skip=GLOB     with -r, do not solve the files whose name matches GLOB,
+                    such as output files (default 'output*'); none if empty
+    --help          display this message and exit
+    --version       display the version and exit
+
+Environment:
+    NO_COLOR        if set and not empty, disables colors when WHEN is 'auto'
+    CLICOLOR_FORCE  if set and not '0', enables colors when WHEN is 'auto'
 ";
 
 fn main() {
     let args: Vec<_> = std::env::args().skip(1).collect();
     if args.iter().any(|s| s == "--help") {
-        print!("{}", USAGE_STRING);
+        print!("{USAGE_STRING}");
         return;
     }
     if args.iter().any(|s| s == "--version") {
-        println!("takuzu {}", VERSION);
+        println!("takuzu {VERSION}");
         return;
     }
-    if args.iter().filter(|&s| s == "-").count() > 1 {
+    let (verify, args) = match args.first().map(String::as_str) {
+        Some("verify") => (true, &args[1..]),
+        Some("solve") => (false, &args[1..]),
+        _ => (false, &args[..]),
+    };
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(2);
+        }
+    };
+    let stdin = options.inputs.iter().filter(|&input| matches!(input, Input::File(s) if s == "-"));
+    if stdin.count() > 1 {
         eprintln!("error: '-' (stdin) must not be mentionned more than once");
+        std::process::exit(2);
+    }
+    if options.animate && options.jobs.is_some() {
+        eprintln!("error: '--animate' cannot be used with '--jobs'");
+        std::process::exit(2);
+    }
+    if verify {
+        let theme = options.color.enabled(libc::STDOUT_FILENO).then_some(options.theme);
+        match run_verify(&options, theme.as_ref()) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(err) => {
+                eprintln!("error{}", DisplayCauses(err));
+                std::process::exit(1);
+            }
+        }
         return;
     }
-    if args.is_empty() {
-        run("-");
+    let style = Style {
+        theme: options.color.enabled(libc::STDOUT_FILENO).then_some(options.theme),
+        animation_theme: options.color.enabled(libc::STDERR_FILENO).then_some(options.theme),
+        pretty: options.pretty,
+        indices: options.indices,
+        blocks: options.blocks,
+        stats: options.stats,
+        animate: options.animate.then_some(options.delay),
+    };
+    let (files, mut success) = if options.inputs.is_empty() {
+        (vec!["-".to_owned()], true)
+    } else {
+        let filter = match Filter::new(&options) {
+            Ok(filter) => filter,
+            Err(err) => {
+                eprintln!("error: {err}");
+                std::process::exit(2);
+            }
+        };
+        list_files(&options.inputs, &filter)
+    };
+    match options.jobs {
+        Some(jobs) => {
+            success &= run_jobs(&files, jobs, options.rules, &options.limits, &style);
+        }
+        None => {
+            for (k, filename) in files.iter().enumerate() {
+                if k != 0 {
+                    println!();
+                }
+                let report = run(filename, options.rules, &options.limits, &style);
+                report.print();
+                success &= !report.status.is_failure();
+            }
+        }
+    }
+    if !success {
+        std::process::exit(1);
+    }
+}
+
+/// Command line options.
+#[allow(clippy::struct_excessive_bools)]
+struct Options {
+    color: ColorChoice,
+    theme: Theme,
+    pretty: bool,
+    indices: bool,
+    blocks: usize,
+    stats: bool,
+    animate: bool,
+    delay: Duration,
+    jobs: Option<usize>,
+    rules: RuleOptions,
+    limits: SolveOptions,
+    inputs: Vec<Input>,
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+    skip: Option<Glob>,
+}
+
+/// A file or a directory to search for files, from the command line.
+enum Input {
+    File(String),
+    Dir(String),
+}
+
+impl Options {
+    /// Parses the command line arguments, `--help` and `--version` excepted.
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            color: ColorChoice::Auto,
+            theme: Theme::DEFAULT,
+            pretty: false,
+            indices: false,
+            blocks: 0,
+            stats: false,
+            animate: false,
+            delay: Duration::from_millis(50),
+            jobs: None,
+            rules: RuleOptions::default(),
+            limits: SolveOptions::new(),
+            inputs: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            skip: Some(Glob::new("output*").expect("the default pattern is valid")),
+        };
+        let glob = |pattern: &str, option: &str| {
+            Glob::new(pattern)
+                .map_err(|err| format!("invalid argument '{pattern}' for '{option}': {err}"))
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if let Some(when) = value(arg, "--color", &mut args)? {
+                options.color = match when {
+                    "auto" => ColorChoice::Auto,
+                    "always" => ColorChoice::Always,
+                    "never" => ColorChoice::Never,
+                    _ => return Err(format!("invalid argument '{when}' for '--color'")),
+                };
+            } else if let Some(name) = value(arg, "--theme", &mut args)? {
+                options.theme = Theme::named(name)
+                    .ok_or_else(|| format!("invalid argument '{name}' for '--theme'"))?;
+            } else if let Some(n) = value(arg, "--blocks", &mut args)? {
+                options.blocks =
+                    n.parse().map_err(|_| format!("invalid argument '{n}' for '--blocks'"))?;
+            } else if let Some(n) = value(arg, "--max-run", &mut args)? {
+                let max_run = match n {
+                    "none" => None,
+                    _ => Some(positive(n, "--max-run")?),
+                };
+                options.rules.update(|rules| rules.with_max_run(max_run));
+            } else if let Some(mode) = value(arg, "--balance", &mut args)? {
+                let balance = match mode {
+                    "equal" => Balance::Equal,
+                    "relaxed" => Balance::Relaxed,
+                    "free" => Balance::Free,
+                    _ => return Err(format!("invalid argument '{mode}' for '--balance'")),
+                };
+                options.rules.update(|rules| rules.with_balance(balance));
+            } else if let Some(n) = value(arg, "--max-solutions", &mut args)? {
+                options.limits = options.limits.with_max_solutions(positive(n, "--max-solutions")?);
+            } else if let Some(secs) = value(arg, "--timeout", &mut args)? {
+                let timeout = secs
+                    .parse()
+                    .ok()
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                    .ok_or_else(|| format!("invalid argument '{secs}' for '--timeout'"))?;
+                options.limits = options.limits.with_timeout(timeout);
+            } else if let Some(ms) = value(arg, "--delay", &mut args)? {
+                let ms =
+                    ms.parse().map_err(|_| format!("invalid argument '{ms}' for '--delay'"))?;
+                options.delay = Duration::from_millis(ms);
+            } else if let Some(n) = value(arg, "--jobs", &mut args)? {
+                options.jobs = Some(positive(n, "--jobs")?);
+            } else if arg == "--duplicates" {
+                options.rules.update(|rules| rules.with_unique_lines(false));
+            } else if arg == "--tango" {
+                options.rules.force_tango = true;
+            } else if arg == "--pretty" {
+                options.pretty = true;
+            } else if arg == "--indices" {
+                options.indices = true;
+            } else if arg == "--stats" {
+                options.stats = true;
+            } else if arg == "--animate" {
+                options.animate = true;
+            } else if let Some(dir) = value(arg, "-r", &mut args)? {
+                options.inputs.push(Input::Dir(dir.to_owned()));
+            } else if let Some(dir) = value(arg, "--recursive", &mut args)? {
+                options.inputs.push(Input::Dir(dir.to_owned()));
+            } else if let Some(pattern) = value(arg, "--include", &mut args)? {
+                options.include.push(glob(pattern, "--include")?);
+            } else if let Some(pattern) = value(arg, "--exclude", &mut args)? {
+                options.exclude.push(glob(pattern, "--exclude")?);
+            } else if let Some(pattern) = value(arg, "--skip", &mut args)? {
+                options.skip =
+                    if pattern.is_empty() { None } else { Some(glob(pattern, "--skip")?) };
+            } else if arg.starts_with("--") {
+                return Err(format!("unrecognized option '{arg}'"));
+            } else {
+                options.inputs.push(Input::File(arg.clone()));
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Returns the argument of the option `name` if `arg` is that option,
+/// given as `name=VALUE`, or as `name` followed by `VALUE` in `rest`.
+fn value<'a>(
+    arg: &'a str,
+    name: &str,
+    rest: &mut impl Iterator<Item = &'a String>,
+) -> Result<Option<&'a str>, String> {
+    if let Some(value) = arg.strip_prefix(name).and_then(|s| s.strip_prefix('=')) {
+        Ok(Some(value))
+    } else if arg == name {
+        let value = rest.next().ok_or_else(|| format!("option '{name}' requires an argument"))?;
+        Ok(Some(value))
     } else {
-        run(&args[0]);
-        for filename in &args[1..] {
-            println!();
-            run(filename);
+        Ok(None)
+    }
+}
+
+/// The rules chosen on the command line, for each variant a grid
+/// may be written for.
+#[derive(Clone, Copy)]
+struct RuleOptions {
+    takuzu: RuleSet,
+    tango: RuleSet,
+    /// Whether all grids follow the rules of the Tango variant.
+    force_tango: bool,
+}
+
+impl Default for RuleOptions {
+    fn default() -> Self {
+        Self { takuzu: RuleSet::TAKUZU, tango: RuleSet::TANGO, force_tango: false }
+    }
+}
+
+impl RuleOptions {
+    /// Changes the rules of every variant.
+    fn update(&mut self, f: impl Fn(RuleSet) -> RuleSet) {
+        self.takuzu = f(self.takuzu);
+        self.tango = f(self.tango);
+    }
+
+    /// Returns the rules for the grid written as `s`.
+    fn for_grid(self, s: &str) -> RuleSet {
+        if self.force_tango || RuleSet::detect(s) == RuleSet::TANGO {
+            self.tango
+        } else {
+            self.takuzu
+        }
+    }
+}
+
+/// Parses the argument of an option taking a positive number.
+fn positive(n: &str, option: &str) -> Result<usize, String> {
+    match n.parse() {
+        Ok(0) | Err(_) => Err(format!("invalid argument '{n}' for '{option}'")),
+        Ok(n) => Ok(n),
+    }
+}
+
+/// Which files to solve in the directories given with `-r`.
+struct Filter {
+    /// Patterns matching the paths of the files to solve, if any.
+    include: Option<GlobSet>,
+    /// Patterns matching the paths of the files not to solve.
+    exclude: GlobSet,
+    /// Pattern matching the names of the files not to solve.
+    skip: Option<GlobMatcher>,
+}
+
+impl Filter {
+    fn new(options: &Options) -> Result<Self, globset::Error> {
+        let set = |globs: &[Glob]| {
+            globs.iter().fold(&mut GlobSetBuilder::new(), |set, glob| set.add(glob.clone())).build()
+        };
+        Ok(Self {
+            include: if options.include.is_empty() { None } else { Some(set(&options.include)?) },
+            exclude: set(&options.exclude)?,
+            skip: options.skip.as_ref().map(Glob::compile_matcher),
+        })
+    }
+
+    /// Returns `true` if the file at `path` in a directory is to be solved.
+    fn matches(&self, path: &Path) -> bool {
+        let skipped = |name| self.skip.as_ref().is_some_and(|skip| skip.is_match(name));
+        self.include.as_ref().map_or(true, |include| include.is_match(path))
+            && !self.exclude.is_match(path)
+            && !path.file_name().is_some_and(skipped)
+    }
+}
+
+/// Returns the files to solve: the files given on the command line, and
+/// those found in the directories, sorted by name.
+///
+/// Reports the directories that cannot be read, and returns `false` with
+/// the files if there are any.
+fn list_files(inputs: &[Input], filter: &Filter) -> (Vec<String>, bool) {
+    let mut files = Vec::new();
+    let mut success = true;
+    for input in inputs {
+        match input {
+            Input::File(filename) => files.push(filename.clone()),
+            Input::Dir(dir) => {
+                for entry in walkdir::WalkDir::new(dir).sort_by_file_name() {
+                    match entry {
+                        Ok(entry) => {
+                            let path = entry.path();
+                            let relative = path.strip_prefix(dir).unwrap_or(path);
+                            if entry.file_type().is_file() && filter.matches(relative) {
+                                files.push(path.display().to_string());
+                            }
+                        }
+                        Err(err) => {
+                            let path = err.path().unwrap_or_else(|| Path::new(dir)).to_owned();
+                            // The I/O error alone, as the walk error repeats it.
+                            let err = err
+                                .into_io_error()
+                                .map_or_else(|| Error::msg("file system loop found"), Error::from);
+                            eprintln!("error: {}{}", path.display(), DisplayCauses(err));
+                            success = false;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    (files, success)
+}
+
+/// When to colorize the output.
+#[derive(Clone, Copy)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns `true` if the output should be colorized.
+    ///
+    /// In `Auto` mode, a non-empty `NO_COLOR` disables colors,
+    /// a `CLICOLOR_FORCE` other than `0` enables them,
+    /// otherwise colors are used if the output `fd` is a terminal.
+    fn enabled(self, fd: libc::c_int) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+                    false
+                } else if std::env::var_os("CLICOLOR_FORCE").is_some_and(|v| v != "0") {
+                    true
+                } else {
+                    isatty(fd)
+                }
+            }
+        }
+    }
+}
+
+/// How to display the grids.
+struct Style {
+    theme: Option<Theme>,
+    /// The theme of the animation, which is drawn on `stderr`.
+    animation_theme: Option<Theme>,
+    pretty: bool,
+    indices: bool,
+    blocks: usize,
+    stats: bool,
+    /// The delay between the steps of the animation, if any.
+    animate: Option<Duration>,
+}
+
+/// The results of solving a file, to be printed once it is done.
+struct Report {
+    /// What to print to `stdout`.
+    output: String,
+    /// What to print to `stderr`.
+    errors: String,
+    status: Status,
+    /// Time spent reading, parsing and solving the file.
+    elapsed: Duration,
+}
+
+impl Report {
+    fn print(&self) {
+        print!("{}", self.output);
+        eprint!("{}", self.errors);
+    }
+}
+
+/// How solving a file went.
+#[derive(Clone, Copy)]
+enum Status {
+    /// The search found `count` solutions, and all of them if `complete`.
+    Solved { count: usize, complete: bool },
+    /// The grid breaks the rules.
+    Illegal,
+    /// The file is not a valid grid.
+    ParseError,
+    /// The file could not be read.
+    ReadError,
+}
+
+impl Status {
+    /// Returns `true` if the file could not be solved: it could not be read
+    /// or parsed, breaks the rules, or has no solution.
+    const fn is_failure(self) -> bool {
+        !matches!(self, Self::Solved { count, complete } if count > 0 || !complete)
+    }
+}
+
+fn run(filename: &str, rules: RuleOptions, limits: &SolveOptions, style: &Style) -> Report {
+    /// Writing to a `String` does not fail.
+    const INFALLIBLE: &str = "failed to write to a string";
+
+    let start = Instant::now();
+    let (mut output, mut errors) = (String::new(), String::new());
+    let status = match solve_file(filename, rules, limits, style) {
+        Ok((grid, outcome)) => {
+            write_solutions(&mut output, filename, &grid, &outcome.solutions, style)
+                .expect(INFALLIBLE);
+            let name = display_name(filename);
+            if let Some(reason) = outcome.stopped {
+                writeln!(errors, "warning: {name}: search stopped early: {reason}")
+                    .expect(INFALLIBLE);
+            }
+            if style.stats {
+                write!(errors, "{name}: statistics\n{}", outcome.stats).expect(INFALLIBLE);
+            }
+            Status::Solved { count: outcome.solutions.len(), complete: outcome.is_complete() }
+        }
+        Err(err) => {
+            let status = if err.downcast_ref::<GridError>().is_some() {
+                Status::Illegal
+            } else if err.downcast_ref::<GridParseError>().is_some() {
+                Status::ParseError
+            } else {
+                Status::ReadError
+            };
+            writeln!(errors, "error: {}{}", filename, DisplayCauses(err)).expect(INFALLIBLE);
+            status
+        }
+    };
+    Report { output, errors, status, elapsed: start.elapsed() }
+}
+
+/// Solves the files on `jobs` threads, printing the reports in the order
+/// of the files as soon as possible, then a summary to `stderr`.
+///
+/// Returns `true` if none of the files failed.
+fn run_jobs(
+    files: &[String],
+    jobs: usize,
+    rules: RuleOptions,
+    limits: &SolveOptions,
+    style: &Style,
+) -> bool {
+    let start = Instant::now();
+    let next = AtomicUsize::new(0);
+    let mut summary = Summary::default();
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..jobs.min(files.len()) {
+            let (sender, next) = (sender.clone(), &next);
+            scope.spawn(move || loop {
+                let k = next.fetch_add(1, Ordering::Relaxed);
+                let Some(filename) = files.get(k) else { break };
+                if sender.send((k, run(filename, rules, limits, style))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        // Reports that are ready but wait for those of earlier files.
+        let mut waiting = BTreeMap::new();
+        let mut printed = 0;
+        for (k, report) in receiver {
+            waiting.insert(k, report);
+            while let Some(report) = waiting.remove(&printed) {
+                if printed != 0 {
+                    println!();
+                }
+                report.print();
+                summary.add(display_name(&files[printed]), &report);
+                printed += 1;
+            }
+        }
+    });
+    summary.wall = start.elapsed();
+    eprint!("{summary}");
+    summary.failures == 0
+}
+
+/// Counts of the outcomes of a batch of files, and times spent on them.
+#[derive(Default)]
+struct Summary {
+    files: usize,
+    solved: usize,
+    unique: usize,
+    multiple: usize,
+    no_solution: usize,
+    stopped: usize,
+    illegal: usize,
+    parse_errors: usize,
+    read_errors: usize,
+    /// Number of files that could not be solved, see [`Status::is_failure`].
+    failures: usize,
+    /// Sum of the times spent on each file.
+    total: Duration,
+    /// Time spent on the batch.
+    wall: Duration,
+    /// The file that took the longest, with its time.
+    slowest: Option<(String, Duration)>,
+}
+
+impl Summary {
+    fn add(&mut self, filename: &str, report: &Report) {
+        self.files += 1;
+        self.failures += usize::from(report.status.is_failure());
+        match report.status {
+            Status::Solved { count, complete } => {
+                self.solved += usize::from(count > 0);
+                self.unique += usize::from(complete && count == 1);
+                self.multiple += usize::from(count > 1);
+                self.no_solution += usize::from(complete && count == 0);
+                self.stopped += usize::from(!complete);
+            }
+            Status::Illegal => self.illegal += 1,
+            Status::ParseError => self.parse_errors += 1,
+            Status::ReadError => self.read_errors += 1,
+        }
+        self.total += report.elapsed;
+        if self.slowest.as_ref().map_or(true, |&(_, slowest)| report.elapsed > slowest) {
+            self.slowest = Some((filename.to_owned(), report.elapsed));
+        }
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "summary")?;
+        let counts = [
+            ("files", self.files),
+            ("solved", self.solved),
+            ("unique", self.unique),
+            ("multiple", self.multiple),
+            ("no solution", self.no_solution),
+            ("stopped early", self.stopped),
+            ("illegal", self.illegal),
+            ("parse errors", self.parse_errors),
+            ("read errors", self.read_errors),
+        ];
+        for (name, count) in counts {
+            writeln!(f, "    {name:<16}{count:>8}")?;
+        }
+        writeln!(f, "    {:<16}{:>8.3?}", "total time", self.total)?;
+        writeln!(f, "    {:<16}{:>8.3?}", "wall time", self.wall)?;
+        if let Some((filename, time)) = &self.slowest {
+            writeln!(f, "    {:<16}{:>8.3?}  {}", "slowest", time, filename)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a file, parses it into a grid following the rules
+/// and returns that grid with the outcome of solving it within the limits.
+/// Animates the search on `stderr` if the style asks for it.
+fn solve_file(
+    filename: &str,
+    rules: RuleOptions,
+    limits: &SolveOptions,
+    style: &Style,
+) -> Result<(Grid, SolveOutcome), Error> {
+    let s = read_to_string(filename)?;
+    let grid = Grid::parse_with_rules(&s, rules.for_grid(&s))?;
+    let outcome = match style.animate {
+        Some(delay) => {
+            let mut animation = Animation::new(&grid, style.animation_theme.as_ref(), delay);
+            eprintln!("{}", display_name(filename));
+            animation.legend();
+            let outcome = grid.solve_observed(limits, &mut animation)?;
+            animation.finish();
+            outcome
+        }
+        None => grid.solve_with(limits)?,
+    };
+    Ok((grid, outcome))
+}
+
+/// Draws the search of the solver on `stderr`, redrawing the grid in place
+/// after each step.
+struct Animation<'a> {
+    grid: Grid,
+    colors: Vec<Option<Color>>,
+    theme: Option<&'a Theme>,
+    delay: Duration,
+    /// Number of lines of the last frame, 0 before the first one.
+    lines: usize,
+    /// The last solution found, with its colors.
+    solved: Option<(Grid, Vec<Option<Color>>)>,
+}
+
+impl<'a> Animation<'a> {
+    fn new(grid: &Grid, theme: Option<&'a Theme>, delay: Duration) -> Self {
+        let (rows, cols) = grid.dimensions();
+        let colors = vec![None; rows * cols];
+        Self { grid: grid.clone(), colors, theme, delay, lines: 0, solved: None }
+    }
+
+    /// Returns the color of the cells filled by `rule`, or of the guesses
+    /// if `rule` is `None`, if colors are enabled.
+    fn color(&self, rule: Option<Rule>) -> Option<Color> {
+        let theme = self.theme?;
+        Some(rule.map_or(theme.guess, |rule| {
+            let index = Rule::ALL.iter().position(|&other| other == rule).unwrap_or_default();
+            theme.rules[index]
+        }))
+    }
+
+    /// Prints the names of the rules in their colors, if colors are enabled.
+    fn legend(&self) {
+        if let Some(theme) = self.theme {
+            let names = Rule::ALL.iter().map(ToString::to_string).chain(Some("guess".to_owned()));
+            let legend = names
+                .zip(theme.rules.iter().chain(Some(&theme.guess)))
+                .map(|(name, color)| format!("{color}{name}\u{1b}[0m"))
+                .collect::<Vec<_>>();
+            eprintln!("{}", legend.join("  "));
+        }
+    }
+
+    /// Draws the last solution found again, if any, as the search may have
+    /// gone on after it.
+    fn finish(&mut self) {
+        if let Some((grid, colors)) = self.solved.take() {
+            self.grid = grid;
+            self.colors = colors;
+            self.draw(false);
+        }
+    }
+
+    /// Draws the grid over the previous frame, then waits.
+    ///
+    /// If `flash` is `true`, the colored cells are drawn in the error color.
+    fn draw(&mut self, flash: bool) {
+        let frame = if flash {
+            let error = self.theme.map(|theme| theme.error);
+            let colors: Vec<_> = self.colors.iter().map(|color| color.and(error)).collect();
+            PaintedGrid(&self.grid, &colors).to_string()
+        } else {
+            PaintedGrid(&self.grid, &self.colors).to_string()
+        };
+        let mut stderr = std::io::stderr().lock();
+        if self.lines > 0 {
+            let _ = write!(stderr, "\u{1b}[{}A\r", self.lines);
+        }
+        let _ = write!(stderr, "{frame}");
+        let _ = stderr.flush();
+        self.lines = frame.lines().count();
+        std::thread::sleep(self.delay);
+    }
+}
+
+impl SolveObserver for Animation<'_> {
+    fn on_assign(&mut self, coord: (usize, usize), value: Cell, rule: Rule) {
+        let (_, cols) = self.grid.dimensions();
+        self.grid[coord] = value;
+        self.colors[coord.0 * cols + coord.1] = self.color(Some(rule));
+        self.draw(false);
+    }
+
+    fn on_guess(&mut self, grid: &Grid, coord: (usize, usize)) {
+        let (_, cols) = grid.dimensions();
+        self.grid = grid.clone();
+        // Cells emptied since the last frame lose their colors, the others
+        // were filled before the guess and keep theirs.
+        for (color, cell) in self.colors.iter_mut().zip(grid.as_slice()) {
+            if cell.is_empty() {
+                *color = None;
+            }
         }
+        self.colors[coord.0 * cols + coord.1] = self.color(None);
+        self.draw(false);
+    }
+
+    fn on_backtrack(&mut self, grid: &Grid) {
+        self.grid = grid.clone();
+        self.draw(true);
+    }
+
+    fn on_solution(&mut self, solution: &Grid) {
+        self.grid = solution.clone();
+        self.solved = Some((self.grid.clone(), self.colors.clone()));
+        self.draw(false);
     }
 }
 
-fn run(filename: &str) {
-    match solve_file(filename) {
-        Ok((grid, solutions)) => print_solutions(filename, &grid, &solutions),
-        Err(err) => eprintln!("error: {}{}", filename, DisplayCauses(err)),
+/// Solves a grid and compares its solutions with the expected ones,
+/// printing the differences. Returns `true` if there are none.
+fn run_verify(options: &Options, theme: Option<&Theme>) -> Result<bool, Error> {
+    let [Input::File(filename), Input::File(expected)] = options.inputs.as_slice() else {
+        anyhow::bail!("'verify' requires a GRID and an EXPECTED file");
+    };
+    let grid = read_to_string(filename)
+        .map_err(Error::from)
+        .and_then(|s| Ok(Grid::parse_with_rules(&s, options.rules.for_grid(&s))?))
+        .with_context(|| display_name(filename).to_owned())?;
+    let expected = read_to_string(expected)
+        .map_err(Error::from)
+        .and_then(|s| Ok(grid.parse_solutions(&s)?))
+        .with_context(|| display_name(expected).to_owned())?;
+    let verification = grid.verify(&expected).with_context(|| display_name(filename).to_owned())?;
+    let filename = display_name(filename);
+    if verification.is_ok() {
+        println!("{filename}: {} solution(s) as expected", expected.len());
+        return Ok(true);
     }
+    let (missing, extra) = (&verification.missing, &verification.extra);
+    println!(
+        "{filename}: {} missing solution(s), {} extra solution(s)",
+        missing.len(),
+        extra.len()
+    );
+    let print_diffs = |grids: &[Grid], kind: &str, others: &[Grid], of_others: &str| {
+        for (k, solution) in grids.iter().enumerate() {
+            // The differences with the closest grid of the other side,
+            // or with the puzzle if there is none.
+            let closest = others.iter().min_by_key(|other| distance(solution, other));
+            let (reference, name) =
+                closest.map_or((&grid, "the puzzle"), |other| (other, of_others));
+            println!("\n{kind} solution {}, compared with {name}:", k + 1);
+            match theme {
+                Some(theme) => print!("{}", AnsiGridDiff(reference, solution).with_theme(theme)),
+                None => print!("{solution}"),
+            }
+        }
+    };
+    print_diffs(missing, "missing", extra, "the closest extra solution");
+    print_diffs(extra, "extra", missing, "the closest missing solution");
+    Ok(false)
 }
 
-/// Reads a file, parses it into a grid and returns that grid with its
-/// solutions.
-fn solve_file(filename: &str) -> Result<(Grid, Vec<Grid>), Error> {
-    let grid: Grid = read_to_string(filename)?.parse()?;
-    let solutions = grid.solve()?;
-    Ok((grid, solutions))
+/// Returns the number of cells that differ between two grids.
+fn distance(a: &Grid, b: &Grid) -> usize {
+    a.as_slice().iter().zip(b.as_slice()).filter(|(a, b)| a != b).count()
 }
 
 /// Reads the contents of a file into a string,
@@ -76,34 +855,59 @@ fn read_to_string(filename: &str) -> std::io::Result<String> {
     }
 }
 
-/// Prints a grid's solution(s) to `stdout`.
+/// Writes a grid's solution(s) to `out`.
 ///
-/// If `stdout` is a terminal, prints the grids with colors highlighting the
+/// If the style has a theme, writes the grids with colors highlighting the
 /// differences with the unsolved original grid.
-fn print_solutions(mut filename: &str, grid: &Grid, solutions: &[Grid]) {
+fn write_solutions(
+    out: &mut String,
+    filename: &str,
+    grid: &Grid,
+    solutions: &[Grid],
+    style: &Style,
+) -> fmt::Result {
     #[inline]
-    fn print_loop<'a, D>(filename: &str, solutions: &'a [Grid], format: impl Fn(&'a Grid) -> D)
+    fn write_loop<'a, D>(
+        out: &mut String,
+        filename: &str,
+        solutions: &'a [Grid],
+        format: impl Fn(&'a Grid) -> D,
+    ) -> fmt::Result
     where D: Display {
         match solutions {
-            [] => println!("{}: no solution", filename),
-            [solution] => print!("{}\n{}", filename, format(solution)),
+            [] => writeln!(out, "{filename}: no solution"),
+            [solution] => write!(out, "{}\n{}", filename, format(solution)),
             [solution, solutions @ ..] => {
-                print!("{}: 1\n{}", filename, format(solution));
+                write!(out, "{}: 1\n{}", filename, format(solution))?;
                 for (i, solution) in solutions.iter().enumerate() {
-                    print!("\n{}: {}\n{}", filename, i + 2, format(solution));
+                    write!(out, "\n{}: {}\n{}", filename, i + 2, format(solution))?;
                 }
+                Ok(())
             }
         }
     }
 
-    if filename == "-" {
-        filename = "(stdin)";
+    let filename = display_name(filename);
+    match (&style.theme, style.pretty) {
+        (Some(theme), true) => write_loop(out, filename, solutions, |solution| {
+            solution.pretty().indices(style.indices).blocks(style.blocks).diff(grid, theme)
+        }),
+        (None, true) => write_loop(out, filename, solutions, |solution| {
+            solution.pretty().indices(style.indices).blocks(style.blocks)
+        }),
+        (Some(theme), false) => write_loop(out, filename, solutions, |solution| {
+            AnsiGridDiff(grid, solution).with_theme(theme)
+        }),
+        (None, false) => write_loop(out, filename, solutions, |solution| solution),
+    }
+}
+
+/// Returns the name under which a file is displayed, "(stdin)" for "-".
+fn display_name(filename: &str) -> &str {
+    match filename {
+        "-" => "(stdin)",
+        _ => filename,
     }
-    if isatty_stdout() {
-        print_loop(filename, solutions, |solution| AnsiGridDiff(grid, solution));
-    } else {
-        print_loop(filename, solutions, |solution| solution);
-    };
 }
 
 /// Displays the causes of an `Error` recursively.
@@ -112,13 +916,13 @@ struct DisplayCauses(Error);
 impl Display for DisplayCauses {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for cause in self.0.chain() {
-            write!(f, ": {}", cause)?;
+            write!(f, ": {cause}")?;
         }
         Ok(())
     }
 }
 
-/// Returns `true` if `stdout` is a terminal.
-fn isatty_stdout() -> bool {
-    matches!(unsafe { libc::isatty(libc::STDOUT_FILENO) }, 1)
+/// Returns `true` if the file descriptor `fd` is a terminal.
+fn isatty(fd: libc::c_int) -> bool {
+    matches!(unsafe { libc::isatty(fd) }, 1)
 }
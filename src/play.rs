@@ -0,0 +1,462 @@
+#![allow(clippy::module_name_repetitions)]
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display},
+    time::{Duration, Instant},
+};
+
+use crate::{Cell, Grid, GridError, LegalityTracker, SolveOptions, StopReason};
+use log::MoveLog;
+
+pub mod log;
+mod save;
+
+/// The candidate values pencilled in a cell.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Marks {
+    /// `0` is a candidate.
+    pub zero: bool,
+    /// `1` is a candidate.
+    pub one: bool,
+}
+
+impl Marks {
+    /// No candidate marked.
+    pub const NONE: Self = Self { zero: false, one: false };
+
+    /// Returns `true` if `value` is marked as a candidate.
+    /// An `Empty` value is never marked.
+    #[must_use]
+    pub const fn contains(self, value: Cell) -> bool {
+        match value {
+            Cell::Zero => self.zero,
+            Cell::One => self.one,
+            Cell::Empty => false,
+        }
+    }
+
+    /// Returns the marks with `value` added or removed.
+    /// An `Empty` value leaves the marks unchanged.
+    #[must_use]
+    pub const fn with(mut self, value: Cell, marked: bool) -> Self {
+        match value {
+            Cell::Zero => self.zero = marked,
+            Cell::One => self.one = marked,
+            Cell::Empty => {}
+        }
+        self
+    }
+
+    /// Returns `true` if no candidate is marked.
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        !self.zero && !self.one
+    }
+}
+
+/// A change made by the player to a [`PlaySession`].
+///
+/// Each move holds the state before and after the change.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Move {
+    /// The value of a cell changed.
+    Enter {
+        /// Coordinates of the cell.
+        coord: (usize, usize),
+        /// Previous value.
+        old: Cell,
+        /// New value.
+        new: Cell,
+    },
+    /// The candidate marks of a cell changed.
+    Mark {
+        /// Coordinates of the cell.
+        coord: (usize, usize),
+        /// Previous marks.
+        old: Marks,
+        /// New marks.
+        new: Marks,
+    },
+    /// The note attached to a cell changed.
+    Note {
+        /// Coordinates of the cell.
+        coord: (usize, usize),
+        /// Previous note.
+        old: Option<String>,
+        /// New note.
+        new: Option<String>,
+    },
+}
+
+impl Move {
+    /// Returns the coordinates of the cell the move applies to.
+    #[must_use]
+    pub const fn coord(&self) -> (usize, usize) {
+        match *self {
+            Self::Enter { coord, .. } | Self::Mark { coord, .. } | Self::Note { coord, .. } => {
+                coord
+            }
+        }
+    }
+}
+
+/// An error returned by a [`PlaySession`].
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub enum PlayError {
+    /// The puzzle breaks the rules.
+    Illegal,
+    /// The puzzle has no solution.
+    NoSolution,
+    /// The puzzle has more than one solution.
+    MultipleSolutions,
+    /// The search for the solution of the puzzle stopped early,
+    /// before telling whether it is unique.
+    Stopped(StopReason),
+    /// The cell at the given coordinates is a given and cannot be changed.
+    Given((usize, usize)),
+    /// A saved game could not be loaded.
+    /// The field contains the line on which the error occured.
+    InvalidSave(usize),
+}
+
+impl Error for PlayError {}
+
+impl Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Illegal => write!(f, "puzzle is illegal"),
+            Self::NoSolution => write!(f, "puzzle has no solution"),
+            Self::MultipleSolutions => write!(f, "puzzle has more than one solution"),
+            Self::Stopped(reason) => write!(f, "search for the solution stopped early: {reason}"),
+            Self::Given((i, j)) => write!(f, "cell ({i}, {j}) is a given"),
+            Self::InvalidSave(line) => write!(f, "invalid saved game (line {line})"),
+        }
+    }
+}
+
+impl From<GridError> for PlayError {
+    fn from(err: GridError) -> Self {
+        match err {
+            GridError::Illegal => Self::Illegal,
+        }
+    }
+}
+
+/// A player's attempt at solving a puzzle.
+///
+/// The cells filled in the puzzle are the givens and cannot be changed.
+/// The player fills the other cells, may pencil candidate [`Marks`] and
+/// notes in them, and can check the entries against the solution without
+/// it being revealed. Every change is recorded as a [`Move`] in a
+/// [`MoveLog`], so that it can be undone and redone.
+///
+/// A session also keeps track of the time spent playing, and can be saved
+/// to text with [`save`](PlaySession::save) to be resumed later with
+/// [`load`](PlaySession::load).
+///
+/// ```
+/// use takuzu::{Cell, Grid, PlaySession};
+///
+/// let puzzle = "1.\n..\n".parse::<Grid>().unwrap();
+/// let mut session = PlaySession::new(puzzle).unwrap();
+/// session.enter((0, 1), Cell::One).unwrap();
+/// assert_eq!(session.mistakes(), [(0, 1)]);
+/// assert!(session.enter((0, 0), Cell::Zero).is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PlaySession {
+    puzzle: Grid,
+    tracker: LegalityTracker,
+    solution: Grid,
+    marks: Box<[Marks]>,
+    notes: HashMap<(usize, usize), String>,
+    log: MoveLog,
+    elapsed: Duration,
+    resumed: Option<Instant>,
+}
+
+impl PlaySession {
+    /// Starts a session on a puzzle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the puzzle is illegal
+    /// or does not have exactly one solution.
+    pub fn new(puzzle: Grid) -> Result<Self, PlayError> {
+        Self::new_with(puzzle, &SolveOptions::new())
+    }
+
+    /// Starts a session on a puzzle, searching for its solution within
+    /// the limits of `options`. The limit on the number of solutions
+    /// is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the puzzle is illegal, does not have exactly
+    /// one solution, or if the search stopped before telling.
+    pub fn new_with(puzzle: Grid, options: &SolveOptions) -> Result<Self, PlayError> {
+        // A second solution is enough to reject the puzzle.
+        let options = options.clone().with_max_solutions(2);
+        let outcome = puzzle.solve_with(&options)?;
+        let mut solutions = outcome.solutions;
+        let solution = match (solutions.len(), outcome.stopped) {
+            (n, _) if n > 1 => return Err(PlayError::MultipleSolutions),
+            (_, Some(reason)) => return Err(PlayError::Stopped(reason)),
+            (0, None) => return Err(PlayError::NoSolution),
+            (_, None) => solutions.remove(0),
+        };
+        let marks = vec![Marks::NONE; puzzle.as_slice().len()].into_boxed_slice();
+        Ok(Self {
+            tracker: LegalityTracker::new(puzzle.clone()),
+            puzzle,
+            solution,
+            marks,
+            notes: HashMap::new(),
+            log: MoveLog::new(),
+            elapsed: Duration::ZERO,
+            resumed: Some(Instant::now()),
+        })
+    }
+
+    /// Returns the puzzle, i.e. the grid of the givens.
+    #[must_use]
+    pub const fn puzzle(&self) -> &Grid {
+        &self.puzzle
+    }
+
+    /// Returns the current grid, givens and player entries.
+    #[must_use]
+    pub const fn grid(&self) -> &Grid {
+        self.tracker.grid()
+    }
+
+    /// Returns `true` if the current grid does not violate any of the rules.
+    ///
+    /// Unlike [`mistakes`](PlaySession::mistakes), this does not compare
+    /// the entries with the solution. The legality is updated as the cells
+    /// change, so this is cheap enough to be called after every move.
+    #[must_use]
+    pub const fn is_legal(&self) -> bool {
+        self.tracker.is_legal()
+    }
+
+    /// Returns `true` if the cell at `coord` does not violate any of the rules.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    #[must_use]
+    pub fn is_cell_legal(&self, coord: (usize, usize)) -> bool {
+        self.tracker.is_cell_legal(coord)
+    }
+
+    /// Returns `true` if the cell at `coord` is a given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    #[must_use]
+    pub fn is_given(&self, coord: (usize, usize)) -> bool {
+        self.puzzle.as_slice()[self.index(coord)].is_filled()
+    }
+
+    /// Returns the candidates marked in the cell at `coord`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    #[must_use]
+    pub fn marks(&self, coord: (usize, usize)) -> Marks {
+        self.marks[self.index(coord)]
+    }
+
+    /// Returns the note attached to the cell at `coord`, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    #[must_use]
+    pub fn note(&self, coord: (usize, usize)) -> Option<&str> {
+        self.index(coord);
+        self.notes.get(&coord).map(String::as_str)
+    }
+
+    /// Returns the moves leading to the current state, oldest first.
+    #[must_use]
+    pub fn history(&self) -> Vec<&Move> {
+        self.log.path()
+    }
+
+    /// Returns the log of all the moves made, undone ones included.
+    #[must_use]
+    pub const fn log(&self) -> &MoveLog {
+        &self.log
+    }
+
+    /// Undoes the last move. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let m = self.log.undo().cloned();
+        m.map(|m| self.apply(&m, true)).is_some()
+    }
+
+    /// Redoes the move undone last. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let m = self.log.redo().cloned();
+        m.map(|m| self.apply(&m, false)).is_some()
+    }
+
+    /// Redoes the move of the `n`th branch of the log
+    /// (see [`MoveLog::branches`]). Returns `false` if there is no such branch.
+    pub fn redo_branch(&mut self, n: usize) -> bool {
+        let m = self.log.redo_branch(n).cloned();
+        m.map(|m| self.apply(&m, false)).is_some()
+    }
+
+    /// Returns the time spent playing.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed + self.resumed.map_or(Duration::ZERO, |resumed| resumed.elapsed())
+    }
+
+    /// Returns `true` if the clock is stopped.
+    #[must_use]
+    pub const fn is_paused(&self) -> bool {
+        self.resumed.is_none()
+    }
+
+    /// Stops the clock.
+    pub fn pause(&mut self) {
+        if let Some(resumed) = self.resumed.take() {
+            self.elapsed += resumed.elapsed();
+        }
+    }
+
+    /// Restarts the clock. Sessions start with the clock running.
+    pub fn resume(&mut self) {
+        self.resumed.get_or_insert_with(Instant::now);
+    }
+
+    /// Enters a value in the cell at `coord`, or erases it with `Empty`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cell is a given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn enter(&mut self, coord: (usize, usize), value: Cell) -> Result<(), PlayError> {
+        self.check_given(coord)?;
+        let old = self.grid()[coord];
+        if old != value {
+            self.record(Move::Enter { coord, old, new: value });
+        }
+        Ok(())
+    }
+
+    /// Marks or unmarks `value` as a candidate for the cell at `coord`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cell is a given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn mark(
+        &mut self,
+        coord: (usize, usize),
+        value: Cell,
+        marked: bool,
+    ) -> Result<(), PlayError> {
+        self.check_given(coord)?;
+        let old = self.marks(coord);
+        let new = old.with(value, marked);
+        if old != new {
+            self.record(Move::Mark { coord, old, new });
+        }
+        Ok(())
+    }
+
+    /// Attaches a note to the cell at `coord`, or removes it with `None`.
+    /// Givens may hold notes too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn set_note(&mut self, coord: (usize, usize), note: Option<String>) {
+        // Checks the bounds, notes are not stored by index.
+        self.index(coord);
+        let old = self.notes.get(&coord).cloned();
+        if old != note {
+            self.record(Move::Note { coord, old, new: note });
+        }
+    }
+
+    /// Returns the coordinates of the entries that differ from the solution,
+    /// in row-major order. Empty cells are not mistakes.
+    #[must_use]
+    pub fn mistakes(&self) -> Vec<(usize, usize)> {
+        let cols = self.grid().cols();
+        let cells = self.grid().as_slice().iter().zip(self.solution.as_slice());
+        cells
+            .enumerate()
+            .filter(|&(_, (cell, solution))| cell.is_filled() && cell != solution)
+            .map(|(index, _)| (index / cols, index % cols))
+            .collect()
+    }
+
+    /// Returns `true` if the grid is filled and matches the solution.
+    #[must_use]
+    pub fn is_solved(&self) -> bool {
+        *self.grid() == self.solution
+    }
+
+    /// Applies a move, without checking it, and records it in the log.
+    fn record(&mut self, m: Move) {
+        self.apply(&m, false);
+        self.log.push(m);
+    }
+
+    /// Applies a move, or reverts it if `undo` is `true`, without checking it.
+    fn apply(&mut self, m: &Move, undo: bool) {
+        match *m {
+            Move::Enter { coord, old, new } => {
+                self.tracker.set(coord, if undo { old } else { new });
+            }
+            Move::Mark { coord, old, new } => {
+                let index = self.index(coord);
+                self.marks[index] = if undo { old } else { new };
+            }
+            Move::Note { coord, ref old, ref new } => match if undo { old } else { new } {
+                Some(note) => {
+                    self.notes.insert(coord, note.clone());
+                }
+                None => {
+                    self.notes.remove(&coord);
+                }
+            },
+        }
+    }
+
+    /// Returns an error if the cell at `coord` is a given.
+    fn check_given(&self, coord: (usize, usize)) -> Result<(), PlayError> {
+        if self.is_given(coord) {
+            return Err(PlayError::Given(coord));
+        }
+        Ok(())
+    }
+
+    /// Returns the index of the cell at `coord`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    fn index(&self, (i, j): (usize, usize)) -> usize {
+        let (rows, cols) = self.grid().dimensions();
+        assert!(i < rows && j < cols, "cell coordinates out of bounds");
+        i * cols + j
+    }
+}
@@ -0,0 +1,191 @@
+use crate::Move;
+
+/// A history of moves supporting undo and redo.
+///
+/// The log is a tree: making a move after undoing others starts a new
+/// branch instead of discarding them, so every move ever made is kept.
+/// [`redo`](MoveLog::redo) follows the branch visited last, and
+/// [`redo_branch`](MoveLog::redo_branch) any of them.
+///
+/// ```
+/// use takuzu::{Cell, Move, MoveLog};
+///
+/// let enter = |new| Move::Enter { coord: (0, 0), old: Cell::Empty, new };
+/// let mut log = MoveLog::new();
+/// log.push(enter(Cell::Zero));
+/// assert_eq!(log.undo(), Some(&enter(Cell::Zero)));
+/// log.push(enter(Cell::One));
+/// log.undo();
+/// assert_eq!(log.branches().count(), 2);
+/// assert_eq!(log.redo(), Some(&enter(Cell::One)));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct MoveLog {
+    /// The nodes of the tree. The first one is the root and holds no move,
+    /// every other node comes after its parent.
+    nodes: Vec<Node>,
+    /// Index of the node of the last move played, 0 if there is none.
+    current: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+struct Node {
+    m: Option<Move>,
+    parent: usize,
+    children: Vec<usize>,
+    /// The child visited last, followed by `redo`.
+    redo: Option<usize>,
+}
+
+impl Node {
+    const fn new(m: Option<Move>, parent: usize) -> Self {
+        Self { m, parent, children: Vec::new(), redo: None }
+    }
+}
+
+impl Default for MoveLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveLog {
+    /// Creates an empty log.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { nodes: vec![Node::new(None, 0)], current: 0 }
+    }
+
+    /// Returns the total number of moves in the log, all branches included.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    /// Returns `true` if the log holds no move.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Records a move played after the current one.
+    ///
+    /// If the same move had been played from this point and undone,
+    /// its branch is resumed instead of starting a new one.
+    pub fn push(&mut self, m: Move) {
+        let current = self.current;
+        let children = &self.nodes[current].children;
+        let existing = children.iter().copied().find(|&c| self.nodes[c].m.as_ref() == Some(&m));
+        let child = existing.unwrap_or_else(|| {
+            self.nodes.push(Node::new(Some(m), current));
+            let child = self.nodes.len() - 1;
+            self.nodes[current].children.push(child);
+            child
+        });
+        self.nodes[current].redo = Some(child);
+        self.current = child;
+    }
+
+    /// Returns `true` if there is a move to undo.
+    #[must_use]
+    pub const fn can_undo(&self) -> bool {
+        self.current != 0
+    }
+
+    /// Returns `true` if there is a move to redo.
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        self.nodes[self.current].redo.is_some()
+    }
+
+    /// Steps back before the current move and returns it,
+    /// or returns `None` if there is no move to undo.
+    pub fn undo(&mut self) -> Option<&Move> {
+        let current = self.current;
+        if current == 0 {
+            return None;
+        }
+        let parent = self.nodes[current].parent;
+        self.nodes[parent].redo = Some(current);
+        self.current = parent;
+        self.nodes[current].m.as_ref()
+    }
+
+    /// Replays the move undone last from the current point and returns it,
+    /// or returns `None` if there is no move to redo.
+    pub fn redo(&mut self) -> Option<&Move> {
+        let child = self.nodes[self.current].redo?;
+        self.current = child;
+        self.nodes[child].m.as_ref()
+    }
+
+    /// Replays the `n`th of the [`branches`](MoveLog::branches) and returns
+    /// its move, or returns `None` if there is no such branch.
+    pub fn redo_branch(&mut self, n: usize) -> Option<&Move> {
+        let child = *self.nodes[self.current].children.get(n)?;
+        self.nodes[self.current].redo = Some(child);
+        self.current = child;
+        self.nodes[child].m.as_ref()
+    }
+
+    /// Returns the moves that can be redone from the current point,
+    /// one per branch, oldest first.
+    pub fn branches(&self) -> impl Iterator<Item = &Move> {
+        let children = self.nodes[self.current].children.iter();
+        children.filter_map(move |&child| self.nodes[child].m.as_ref())
+    }
+
+    /// Returns the moves leading to the current point, oldest first.
+    #[must_use]
+    pub fn path(&self) -> Vec<&Move> {
+        let mut path = Vec::new();
+        let mut node = self.current;
+        while node != 0 {
+            path.extend(self.nodes[node].m.as_ref());
+            node = self.nodes[node].parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Returns the moves of the log in the order they were first played,
+    /// each with the index of the move it follows (0 if it is a first move,
+    /// moves being numbered from 1).
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (usize, &Move)> {
+        self.nodes.iter().filter_map(|node| node.m.as_ref().map(|m| (node.parent, m)))
+    }
+
+    /// Returns the number of the current move, 0 if there is none.
+    pub(crate) const fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Rebuilds a log from its [`entries`](MoveLog::entries) and the number
+    /// of its current move, as long as each move follows an earlier one.
+    pub(crate) fn from_entries(
+        entries: impl IntoIterator<Item = (usize, Move)>,
+        current: usize,
+    ) -> Option<Self> {
+        let mut log = Self::new();
+        for (parent, m) in entries {
+            let index = log.nodes.len();
+            if parent >= index {
+                return None;
+            }
+            log.nodes.push(Node::new(Some(m), parent));
+            log.nodes[parent].children.push(index);
+            log.nodes[parent].redo = Some(index);
+        }
+        if current >= log.nodes.len() {
+            return None;
+        }
+        log.current = current;
+        let mut node = current;
+        while node != 0 {
+            let parent = log.nodes[node].parent;
+            log.nodes[parent].redo = Some(node);
+            node = parent;
+        }
+        Some(log)
+    }
+}
@@ -0,0 +1,323 @@
+use std::{
+    fmt::{self, Display, Write},
+    time::Duration,
+};
+
+use super::{log::MoveLog, Marks, Move, PlayError, PlaySession};
+use crate::{Balance, Cell, Grid, RuleSet, SolveOptions};
+
+/// First line of a saved game, identifying the format and its version.
+const HEADER: &str = "takuzu save 1";
+
+/// Displays a session in the save format.
+///
+/// ```text
+/// takuzu save 1
+/// rules 2 equal unique
+/// elapsed 83250
+/// moves 3
+/// current 2
+/// 0 enter 0 1 . 1
+/// 1 mark 0 2 -- 0-
+/// 1 note 0 1 - "left of the pair"
+/// puzzle
+/// 1...
+/// ....
+/// ```
+///
+/// The rules are the maximum run length (or `none`), the balance and
+/// `unique` or `duplicates`. The elapsed time is in milliseconds.
+/// Moves are listed in the order they were first played, numbered from 1,
+/// each prefixed with the number of the move it follows (0 for none).
+/// `current` is the number of the last move of the current state.
+/// The puzzle comes last, in the usual grid format.
+struct Save<'a>(&'a PlaySession);
+
+impl Display for Save<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let session = self.0;
+        let rules = session.puzzle.rules();
+        writeln!(f, "{HEADER}")?;
+        match rules.max_run() {
+            Some(max_run) => write!(f, "rules {max_run}")?,
+            None => write!(f, "rules none")?,
+        }
+        let balance = match rules.balance() {
+            Balance::Equal => "equal",
+            Balance::Relaxed => "relaxed",
+            Balance::Free => "free",
+        };
+        let unique = if rules.unique_lines() { "unique" } else { "duplicates" };
+        writeln!(f, " {balance} {unique}")?;
+        writeln!(f, "elapsed {}", session.elapsed().as_millis())?;
+        writeln!(f, "moves {}", session.log.len())?;
+        writeln!(f, "current {}", session.log.current())?;
+        for (parent, m) in session.log.entries() {
+            write!(f, "{parent} ")?;
+            match *m {
+                Move::Enter { coord: (i, j), old, new } => {
+                    write!(f, "enter {i} {j} {} {}", old.to_char(), new.to_char())?;
+                }
+                Move::Mark { coord: (i, j), old, new } => {
+                    write!(f, "mark {i} {j} ")?;
+                    write_marks(f, old)?;
+                    f.write_char(' ')?;
+                    write_marks(f, new)?;
+                }
+                Move::Note { coord: (i, j), ref old, ref new } => {
+                    write!(f, "note {i} {j} ")?;
+                    write_note(f, old.as_deref())?;
+                    f.write_char(' ')?;
+                    write_note(f, new.as_deref())?;
+                }
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "puzzle")?;
+        write!(f, "{}", session.puzzle)
+    }
+}
+
+impl PlaySession {
+    /// Saves the session to text: the puzzle, the log of the moves
+    /// and the time spent playing.
+    ///
+    /// The marks, notes and entries are not saved as such but rebuilt from
+    /// the moves by [`load`](PlaySession::load).
+    #[must_use]
+    pub fn save(&self) -> String {
+        Save(self).to_string()
+    }
+
+    /// Loads a session saved with [`save`](PlaySession::save),
+    /// with the clock running.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not a valid saved game,
+    /// or if the saved puzzle does not have exactly one solution.
+    pub fn load(s: &str) -> Result<Self, PlayError> {
+        Self::load_with(s, &SolveOptions::new())
+    }
+
+    /// Loads a session saved with [`save`](PlaySession::save),
+    /// searching for the solution of the puzzle within the limits of
+    /// `options`, see [`new_with`](PlaySession::new_with).
+    ///
+    /// A node limit keeps the loading of untrusted saves short.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not a valid saved game,
+    /// if the saved puzzle does not have exactly one solution,
+    /// or if the search stopped before telling.
+    pub fn load_with(s: &str, options: &SolveOptions) -> Result<Self, PlayError> {
+        let lines: Vec<_> = s.lines().collect();
+        let field = |line: usize, key: &str| {
+            let rest = lines.get(line).and_then(|l| l.strip_prefix(key));
+            rest.and_then(|rest| rest.strip_prefix(' ')).ok_or(PlayError::InvalidSave(line + 1))
+        };
+        let number = |line: usize, key: &str| {
+            field(line, key)?.parse::<usize>().map_err(|_| PlayError::InvalidSave(line + 1))
+        };
+        if lines.first() != Some(&HEADER) {
+            return Err(PlayError::InvalidSave(1));
+        }
+        let rules = parse_rules(field(1, "rules")?).ok_or(PlayError::InvalidSave(2))?;
+        let elapsed = field(2, "elapsed")?.parse().map_err(|_| PlayError::InvalidSave(3))?;
+        let len = number(3, "moves")?;
+        let current = number(4, "current")?;
+        let first_move: usize = 5;
+        let puzzle = first_move
+            .checked_add(len)
+            .filter(|&puzzle| puzzle < lines.len())
+            .ok_or(PlayError::InvalidSave(4))?;
+        if lines.get(puzzle) != Some(&"puzzle") {
+            return Err(PlayError::InvalidSave(puzzle + 1));
+        }
+        let grid = lines[puzzle + 1..].iter().fold(String::new(), |grid, line| grid + line + "\n");
+        let grid =
+            Grid::parse_with_rules(&grid, rules).map_err(|_| PlayError::InvalidSave(puzzle + 2))?;
+        let mut session = Self::new_with(grid, options)?;
+        let mut entries = Vec::with_capacity(len);
+        for (k, line) in lines[first_move..puzzle].iter().enumerate() {
+            // Move `k + 1` must follow an earlier move.
+            let entry = parse_entry(line)
+                .filter(|&(parent, ref m)| parent <= k && session.is_valid(m))
+                .ok_or(PlayError::InvalidSave(first_move + 1 + k))?;
+            entries.push(entry);
+        }
+        let log = MoveLog::from_entries(entries, current).ok_or(PlayError::InvalidSave(5))?;
+        session.replay(&log).map_err(|n| PlayError::InvalidSave(first_move + n))?;
+        for m in log.path() {
+            session.apply(m, false);
+        }
+        session.log = log;
+        session.elapsed = Duration::from_millis(elapsed);
+        Ok(session)
+    }
+
+    /// Returns `true` if a move is within bounds and changes no given.
+    fn is_valid(&self, m: &Move) -> bool {
+        let (i, j) = m.coord();
+        let (rows, cols) = self.puzzle.dimensions();
+        i < rows && j < cols && (matches!(m, Move::Note { .. }) || !self.is_given((i, j)))
+    }
+
+    /// Plays and undoes every branch of the log, depth first, to check that
+    /// each move changes from the state it was played in. Returns the number
+    /// of the first move that does not.
+    fn replay(&mut self, log: &MoveLog) -> Result<(), usize> {
+        let entries: Vec<_> = log.entries().collect();
+        let mut children = vec![Vec::new(); entries.len() + 1];
+        for (k, &(parent, _)) in entries.iter().enumerate() {
+            children[parent].push(k + 1);
+        }
+        let mut stack = vec![children[0].iter()];
+        let mut path = Vec::new();
+        while let Some(next) = stack.last_mut() {
+            if let Some(&n) = next.next() {
+                let m = entries[n - 1].1;
+                if !self.is_current(m) {
+                    return Err(n);
+                }
+                self.apply(m, false);
+                stack.push(children[n].iter());
+                path.push(m);
+            } else {
+                stack.pop();
+                if let Some(m) = path.pop() {
+                    self.apply(m, true);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the state a move changes from is the current one.
+    fn is_current(&self, m: &Move) -> bool {
+        match *m {
+            Move::Enter { coord, old, .. } => self.grid()[coord] == old,
+            Move::Mark { coord, old, .. } => self.marks(coord) == old,
+            Move::Note { coord, ref old, .. } => self.note(coord) == old.as_deref(),
+        }
+    }
+}
+
+/// Writes the marks of a cell as two characters, `0` or `-` then `1` or `-`.
+fn write_marks(f: &mut fmt::Formatter<'_>, marks: Marks) -> fmt::Result {
+    f.write_char(if marks.zero { '0' } else { '-' })?;
+    f.write_char(if marks.one { '1' } else { '-' })
+}
+
+/// Writes a note as a quoted, escaped string, or `-` for no note.
+fn write_note(f: &mut fmt::Formatter<'_>, note: Option<&str>) -> fmt::Result {
+    match note {
+        Some(note) => write!(f, "{note:?}"),
+        None => f.write_char('-'),
+    }
+}
+
+/// Parses the rules from the fields of the `rules` line.
+fn parse_rules(s: &str) -> Option<RuleSet> {
+    let mut fields = s.split(' ');
+    let max_run = match fields.next()? {
+        "none" => None,
+        n => Some(n.parse().ok().filter(|&n| n != 0)?),
+    };
+    let balance = match fields.next()? {
+        "equal" => Balance::Equal,
+        "relaxed" => Balance::Relaxed,
+        "free" => Balance::Free,
+        _ => return None,
+    };
+    let unique_lines = match fields.next()? {
+        "unique" => true,
+        "duplicates" => false,
+        _ => return None,
+    };
+    fields.next().is_none().then(|| {
+        RuleSet::TAKUZU.with_max_run(max_run).with_balance(balance).with_unique_lines(unique_lines)
+    })
+}
+
+/// Parses a move line into the number of the move it follows and the move.
+fn parse_entry(s: &str) -> Option<(usize, Move)> {
+    let (parent, s) = s.split_once(' ')?;
+    let (kind, s) = s.split_once(' ')?;
+    let (i, s) = s.split_once(' ')?;
+    let (j, s) = s.split_once(' ')?;
+    let coord = (i.parse().ok()?, j.parse().ok()?);
+    let m = match kind {
+        "enter" => {
+            let (old, new) = s.split_once(' ')?;
+            Move::Enter { coord, old: parse_cell(old)?, new: parse_cell(new)? }
+        }
+        "mark" => {
+            let (old, new) = s.split_once(' ')?;
+            Move::Mark { coord, old: parse_marks(old)?, new: parse_marks(new)? }
+        }
+        "note" => {
+            let (old, s) = parse_note(s)?;
+            let (new, s) = parse_note(s.strip_prefix(' ')?)?;
+            if !s.is_empty() {
+                return None;
+            }
+            Move::Note { coord, old, new }
+        }
+        _ => return None,
+    };
+    Some((parent.parse().ok()?, m))
+}
+
+/// Parses a cell written as `0`, `1` or `.`.
+fn parse_cell(s: &str) -> Option<Cell> {
+    match s {
+        "0" => Some(Cell::Zero),
+        "1" => Some(Cell::One),
+        "." => Some(Cell::Empty),
+        _ => None,
+    }
+}
+
+/// Parses marks written by [`write_marks`].
+fn parse_marks(s: &str) -> Option<Marks> {
+    match s.as_bytes() {
+        [zero @ (b'0' | b'-'), one @ (b'1' | b'-')] => {
+            Some(Marks { zero: *zero == b'0', one: *one == b'1' })
+        }
+        _ => None,
+    }
+}
+
+/// Parses a note written by [`write_note`] at the start of `s`,
+/// returning it with the rest of the string.
+fn parse_note(s: &str) -> Option<(Option<String>, &str)> {
+    if let Some(rest) = s.strip_prefix('-') {
+        return Some((None, rest));
+    }
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    let mut note = String::new();
+    while let Some((k, c)) = chars.next() {
+        match c {
+            '"' => return Some((Some(note), &s[k + 2..])),
+            '\\' => note.push(match chars.next()?.1 {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                c @ ('\\' | '"' | '\'') => c,
+                'u' => {
+                    let rest = chars.as_str().strip_prefix('{')?;
+                    let (hex, _) = rest.split_once('}')?;
+                    let c = char::from_u32(u32::from_str_radix(hex, 16).ok()?)?;
+                    chars.nth(hex.len() + 1)?;
+                    c
+                }
+                _ => return None,
+            }),
+            c => note.push(c),
+        }
+    }
+    None
+}
@@ -0,0 +1,154 @@
+use std::fmt::{self, Display, Write};
+
+use crate::{ansi::write_cell_diff, Edge, Grid, Theme};
+
+/// Displays a grid with Unicode box-drawing characters.
+///
+/// Created by [`Grid::pretty`], or by [`AnsiGridDiff::pretty`] to keep the
+/// colors of the diff.
+/// The plain [`Display`] implementation of [`Grid`] is left unchanged.
+///
+/// Every cell is framed. Optionally, the rows and columns can be numbered
+/// and a thicker line can be drawn every few cells to help reading large grids.
+/// Edge constraints are drawn on the lines between the cells.
+#[derive(Copy, Clone, Debug)]
+pub struct PrettyGrid<'a> {
+    grid: &'a Grid,
+    diff: Option<(&'a Grid, &'a Theme)>,
+    indices: bool,
+    blocks: usize,
+}
+
+impl Grid {
+    /// Returns a `Display`able version of the grid drawn with box-drawing
+    /// characters.
+    #[must_use]
+    pub const fn pretty(&self) -> PrettyGrid<'_> {
+        PrettyGrid { grid: self, diff: None, indices: false, blocks: 0 }
+    }
+}
+
+impl<'a> PrettyGrid<'a> {
+    /// Numbers the rows and the columns if `indices` is `true`.
+    /// Column numbers are shown modulo 1000 to stay aligned on the cells.
+    #[must_use]
+    pub const fn indices(mut self, indices: bool) -> Self {
+        self.indices = indices;
+        self
+    }
+
+    /// Draws a thicker line every `n` cells. No line is thickened if `n` is 0.
+    #[must_use]
+    pub const fn blocks(mut self, n: usize) -> Self {
+        self.blocks = n;
+        self
+    }
+
+    /// Colors the cells that differ from `reference`, like [`AnsiGridDiff`].
+    ///
+    /// Ignored if the grids have different dimensions.
+    #[must_use]
+    pub const fn diff(mut self, reference: &'a Grid, theme: &'a Theme) -> Self {
+        self.diff = Some((reference, theme));
+        self
+    }
+
+    /// Returns `true` if the line before the `k`th of `n` rows or columns
+    /// is thick.
+    const fn is_thick(&self, k: usize, n: usize) -> bool {
+        self.blocks != 0 && k != 0 && k != n && k % self.blocks == 0
+    }
+
+    /// Writes the margin holding the row numbers, if any.
+    fn write_margin(&self, f: &mut fmt::Formatter<'_>, row: Option<usize>) -> fmt::Result {
+        if self.indices {
+            let width = digits(self.grid.rows() - 1);
+            match row {
+                Some(i) => write!(f, "{i:>width$} ")?,
+                None => write!(f, "{:width$} ", "")?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the horizontal line before the `i`th row.
+    fn write_rule(&self, f: &mut fmt::Formatter<'_>, i: usize) -> fmt::Result {
+        let (rows, cols) = self.grid.dimensions();
+        let thick = self.is_thick(i, rows);
+        let (left, right, junctions) = match i {
+            0 => ('┌', '┐', ['┬', '┰']),
+            _ if i == rows => ('└', '┘', ['┴', '┸']),
+            _ if thick => ('┝', '┥', ['┿', '╋']),
+            _ => ('├', '┤', ['┼', '╂']),
+        };
+        let line = if thick { '━' } else { '─' };
+        self.write_margin(f, None)?;
+        f.write_char(left)?;
+        for j in 0..cols {
+            let edge = if i == 0 || i == rows { None } else { self.grid.edge_below((i - 1, j)) };
+            f.write_char(line)?;
+            f.write_char(edge.map_or(line, Edge::to_char))?;
+            f.write_char(line)?;
+            if j + 1 != cols {
+                f.write_char(junctions[usize::from(self.is_thick(j + 1, cols))])?;
+            }
+        }
+        f.write_char(right)?;
+        writeln!(f)
+    }
+
+    /// Writes the cells of the `i`th row.
+    fn write_row(&self, f: &mut fmt::Formatter<'_>, i: usize) -> fmt::Result {
+        let cols = self.grid.cols();
+        let reference =
+            self.diff.filter(|(reference, _)| reference.dimensions() == self.grid.dimensions());
+        self.write_margin(f, Some(i))?;
+        f.write_char('│')?;
+        for j in 0..cols {
+            let cell = self.grid[(i, j)];
+            f.write_char(' ')?;
+            match reference {
+                Some((reference, theme)) => write_cell_diff(f, theme, reference[(i, j)], cell)?,
+                None => f.write_char(cell.to_char())?,
+            }
+            f.write_char(' ')?;
+            if j + 1 != cols {
+                let line = if self.is_thick(j + 1, cols) { '┃' } else { '│' };
+                f.write_char(self.grid.edge_right((i, j)).map_or(line, Edge::to_char))?;
+            }
+        }
+        f.write_char('│')?;
+        writeln!(f)
+    }
+}
+
+impl Display for PrettyGrid<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (rows, cols) = self.grid.dimensions();
+        if self.indices {
+            // The numbers are right-aligned on the cells, which are 4 characters
+            // apart: only the last 3 digits fit without shifting the next numbers.
+            self.write_margin(f, None)?;
+            write!(f, "{:>3}", 0)?;
+            for j in 1..cols {
+                write!(f, " {:>3}", j % 1000)?;
+            }
+            writeln!(f)?;
+        }
+        for i in 0..rows {
+            self.write_rule(f, i)?;
+            self.write_row(f, i)?;
+        }
+        self.write_rule(f, rows)
+    }
+}
+
+/// Returns the number of decimal digits of `n`.
+const fn digits(mut n: usize) -> usize {
+    let mut digits = 1;
+    while n >= 10 {
+        n /= 10;
+        digits += 1;
+    }
+    digits
+}
@@ -0,0 +1,205 @@
+#![warn(rust_2018_idioms)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::cargo)]
+#![warn(clippy::nursery)]
+
+//! Differential tests of the solvers against a naive reference solver.
+
+use std::convert::TryFrom;
+
+use takuzu::{Balance, Cell, Edge, Grid, GridError, RuleSet, SolveOptions};
+
+/// Maximum number of empty cells of the grids given to the reference solver.
+const MAX_EMPTY: usize = 10;
+
+/// Maximum number of empty cells of the grids of at most 36 cells, enough
+/// for the solvers to guess.
+const MAX_EMPTY_SMALL: usize = 20;
+
+/// Maximum number of solutions of the grids compared. Grids with more
+/// solutions, for rules leaving many cells free, are skipped.
+const MAX_SOLUTIONS: usize = 200;
+
+/// Number of random grids to compare the solvers on.
+const GRIDS: usize = 2000;
+
+/// A solver under test.
+type Solver = fn(&Grid) -> Result<Vec<Grid>, GridError>;
+
+/// The solvers compared with the reference solver.
+const SOLVERS: &[(&str, Solver)] = &[
+    ("solve", Grid::solve),
+    ("solve_with", |grid| grid.solve_with(&SolveOptions::new()).map(|outcome| outcome.solutions)),
+    #[cfg(feature = "parallel")]
+    ("par_solve", Grid::par_solve),
+];
+
+/// Solves a grid by trying every assignment of its empty cells,
+/// in lexicographic order, and keeping the legal ones.
+///
+/// The assignments starting like an illegal partial grid are skipped,
+/// and the search stops once more than `max` solutions are found.
+fn brute_force(grid: &Grid, max: usize) -> Result<Vec<Grid>, GridError> {
+    if !grid.is_legal() {
+        return Err(GridError::Illegal);
+    }
+    let empty: Vec<_> = (0..grid.rows())
+        .flat_map(|i| (0..grid.cols()).map(move |j| (i, j)))
+        .filter(|&coord| grid[coord].is_empty())
+        .collect();
+    let mut solutions = Vec::new();
+    assign(&mut grid.clone(), &empty, max, &mut solutions);
+    Ok(solutions)
+}
+
+/// Tries both values of the first `empty` cell of a legal grid, `0` first,
+/// then assigns the other cells, adding the filled grids to `solutions`.
+fn assign(grid: &mut Grid, empty: &[(usize, usize)], max: usize, solutions: &mut Vec<Grid>) {
+    let Some((&coord, rest)) = empty.split_first() else {
+        solutions.push(grid.clone());
+        return;
+    };
+    for value in [Cell::Zero, Cell::One] {
+        grid[coord] = value;
+        if solutions.len() <= max && grid.is_legal() {
+            assign(grid, rest, max, solutions);
+        }
+    }
+    grid[coord] = Cell::Empty;
+}
+
+/// A xorshift pseudo-random number generator, for reproducible tests.
+struct Random(u64);
+
+impl Random {
+    /// Returns a number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        usize::try_from(self.0 % n as u64).unwrap()
+    }
+
+    /// Returns `true` with a probability of `1 / n`.
+    fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    fn cell(&mut self) -> Cell {
+        if self.one_in(2) {
+            Cell::Zero
+        } else {
+            Cell::One
+        }
+    }
+
+    fn coord(&mut self, grid: &Grid) -> (usize, usize) {
+        (self.below(grid.rows()), self.below(grid.cols()))
+    }
+}
+
+/// Returns an empty grid of random dimensions from 2 to 8, with random rules
+/// and, sometimes, random edge constraints.
+fn random_empty_grid(random: &mut Random) -> Grid {
+    let max_run = [None, Some(1), Some(2), Some(2), Some(3)][random.below(5)];
+    let balance =
+        [Balance::Equal, Balance::Equal, Balance::Relaxed, Balance::Free][random.below(4)];
+    let rules = RuleSet::TAKUZU
+        .with_max_run(max_run)
+        .with_balance(balance)
+        .with_unique_lines(!random.one_in(3));
+    let (rows, cols) = (2 + random.below(7), 2 + random.below(7));
+    let mut grid = Grid::with_rules(rows, cols, rules).unwrap_or_else(|_| {
+        Grid::with_rules(rows, cols, rules.with_balance(Balance::Relaxed)).unwrap()
+    });
+    if random.one_in(3) {
+        let edge = |random: &mut Random| {
+            random.one_in(6).then(|| if random.one_in(2) { Edge::Equal } else { Edge::Opposite })
+        };
+        for i in 0..rows {
+            for j in 0..cols {
+                if j + 1 < cols {
+                    grid.set_edge_right((i, j), edge(random));
+                }
+                if i + 1 < rows {
+                    grid.set_edge_below((i, j), edge(random));
+                }
+            }
+        }
+    }
+    grid
+}
+
+/// Fills the empty cells of a grid at random, in order, going back on the
+/// cells breaking the rules. Returns `false`, leaving the grid partially
+/// filled, if that failed or took more than `steps` steps.
+fn random_fill(grid: &mut Grid, random: &mut Random, steps: &mut usize) -> bool {
+    let Some(coord) = grid.next_empty() else { return grid.is_legal() };
+    let first = random.cell();
+    let second = if first == Cell::Zero { Cell::One } else { Cell::Zero };
+    for value in [first, second] {
+        if *steps == 0 {
+            return false;
+        }
+        *steps -= 1;
+        grid[coord] = value;
+        if grid.is_cell_legal(coord) && random_fill(grid, random, steps) {
+            return true;
+        }
+    }
+    grid[coord] = Cell::Empty;
+    false
+}
+
+/// Returns a random grid with at most `MAX_EMPTY` empty cells, or
+/// `MAX_EMPTY_SMALL` if it has at most 36 cells: most often
+/// a random legal grid with some of its cells emptied, sometimes with
+/// a number changed, or `None` if no legal grid was found.
+fn random_grid(random: &mut Random) -> Option<Grid> {
+    let mut grid = random_empty_grid(random);
+    if !random_fill(&mut grid, random, &mut 1000) {
+        return None;
+    }
+    let max_empty = if grid.rows() * grid.cols() <= 36 { MAX_EMPTY_SMALL } else { MAX_EMPTY };
+    for _ in 0..=random.below(max_empty) {
+        let coord = random.coord(&grid);
+        grid[coord] = Cell::Empty;
+    }
+    if random.one_in(2) {
+        let coord = random.coord(&grid);
+        grid[coord] = random.cell();
+    }
+    Some(grid)
+}
+
+#[test]
+fn test_solvers_against_brute_force() {
+    let mut random = Random(0x9e37_79b9_7f4a_7c15);
+    let (mut illegal, mut unsolvable, mut unique, mut multiple) = (0, 0, 0, 0);
+    let (mut tested, mut guessed) = (0, 0);
+    while tested < GRIDS {
+        let Some(grid) = random_grid(&mut random) else { continue };
+        let expected = brute_force(&grid, MAX_SOLUTIONS);
+        if expected.as_ref().is_ok_and(|solutions| solutions.len() > MAX_SOLUTIONS) {
+            continue;
+        }
+        tested += 1;
+        if grid.solve_with_stats().is_ok_and(|(_, stats)| stats.branch_points > 0) {
+            guessed += 1;
+        }
+        match expected.as_deref() {
+            Err(_) => illegal += 1,
+            Ok([]) => unsolvable += 1,
+            Ok([_]) => unique += 1,
+            Ok(_) => multiple += 1,
+        }
+        for &(name, solver) in SOLVERS {
+            assert_eq!(solver(&grid), expected, "{name} on\n{grid}");
+        }
+    }
+    // Make sure that the random grids cover all the cases.
+    let counts = [illegal, unsolvable, unique, multiple];
+    assert!(counts.iter().all(|&count| count > GRIDS / 100), "{:?}", counts);
+    // And that the search is exercised, not only the rules.
+    assert!(guessed > GRIDS / 4, "{} grids needed guesses", guessed);
+}
@@ -3,10 +3,20 @@
 #![warn(clippy::cargo)]
 #![warn(clippy::nursery)]
 
-use std::{fs, path::PathBuf, str};
+use std::{
+    convert::TryFrom,
+    fs,
+    path::PathBuf,
+    str,
+    sync::{atomic::AtomicBool, Arc},
+    time::{Duration, Instant},
+};
 
 use lazy_static::lazy_static;
-use takuzu::Grid;
+use takuzu::{
+    Balance, Cell, Grid, LegalityTracker, Line, Marks, Move, MoveLog, PlayError, PlaySession, Rule,
+    RuleSet, SolveObserver, SolveOptions, StopReason, Verification,
+};
 
 lazy_static! {
     static ref GRIDS_DIR: PathBuf = {
@@ -42,7 +52,640 @@ macro_rules! test_grid {
     };
 }
 
+macro_rules! test_edge_grid {
+    ($test_name:ident, $grid:expr, $output:expr, $rules:expr) => {
+        #[test]
+        fn $test_name() {
+            let input = fs::read_to_string(GRIDS_DIR.join($grid)).unwrap();
+            let output = fs::read_to_string(GRIDS_DIR.join($output)).unwrap();
+            let grid = Grid::parse_with_rules(&input, $rules).unwrap();
+            let solutions = grid.solve().unwrap();
+            // Lines of edge markers may be empty: split the output every
+            // `height` lines and the empty line after them.
+            let height = grid.to_string().lines().count();
+            let lines = output.lines().collect::<Vec<_>>();
+            let reference_solutions = lines.chunks(height + 1).collect::<Vec<_>>();
+            assert_eq!(solutions.len(), reference_solutions.len());
+            for (solution, reference) in solutions.iter().zip(reference_solutions) {
+                assert_eq!(solution.to_string().lines().collect::<Vec<_>>(), reference[..height]);
+                assert!(reference.get(height).map_or(true, |line| line.is_empty()));
+            }
+        }
+    };
+}
+
 test_grid!(test_grid_1, "grid1", "output1");
 test_grid!(test_grid_2, "grid2", "output2");
 test_grid!(test_grid_3, "grid3", "output3");
 test_grid!(test_grid_4, "grid4", "output4");
+test_grid!(test_grid_5, "grid5", "output5");
+test_edge_grid!(test_grid_6, "grid6", "output6", RuleSet::TANGO);
+
+#[test]
+fn test_themes() {
+    use takuzu::{AnsiGridDiff, Color, Theme};
+
+    assert_eq!(Theme::named("default"), Some(Theme::DEFAULT));
+    assert_eq!(Theme::named("colorblind"), Some(Theme::COLORBLIND));
+    assert!(Theme::NAMES.iter().all(|name| Theme::named(name).is_some()));
+    assert_eq!(Theme::named("Default"), None);
+    assert_eq!(Theme::named(""), None);
+    assert_eq!(Color::Standard(1).to_string(), "\u{1b}[31m");
+    assert_eq!(Color::Standard(14).to_string(), "\u{1b}[96m");
+    assert_eq!(Color::Fixed(214).to_string(), "\u{1b}[38;5;214m");
+    assert_eq!(Color::Rgb(0, 128, 255).to_string(), "\u{1b}[38;2;0;128;255m");
+
+    let reference = "0.\n1.\n".parse::<Grid>().unwrap();
+    let grid = "01\n00\n".parse::<Grid>().unwrap();
+    let theme = Theme {
+        zero: Color::Fixed(1),
+        one: Color::Fixed(2),
+        error: Color::Fixed(3),
+        ..Theme::DEFAULT
+    };
+    assert_eq!(
+        AnsiGridDiff(&reference, &grid).with_theme(&theme).to_string(),
+        "0\u{1b}[38;5;2m1\u{1b}[0m\n\u{1b}[38;5;3m0\u{1b}[0m\u{1b}[38;5;1m0\u{1b}[0m\n"
+    );
+    assert_eq!(
+        AnsiGridDiff(&reference, &grid).to_string(),
+        AnsiGridDiff(&reference, &grid).with_theme(&Theme::DEFAULT).to_string()
+    );
+    let other = "0.\n1.\n..\n..\n".parse::<Grid>().unwrap();
+    assert_eq!(AnsiGridDiff(&other, &grid).to_string(), "01\n00\n");
+}
+
+#[test]
+fn test_pretty() {
+    let grid = "01\n1.\n".parse::<Grid>().unwrap();
+    assert_eq!(grid.to_string(), "01\n1.\n");
+    assert_eq!(
+        grid.pretty().indices(true).to_string(),
+        "    0   1
+  ┌───┬───┐
+0 │ 0 │ 1 │
+  ├───┼───┤
+1 │ 1 │ . │
+  └───┴───┘
+"
+    );
+    let rules = RuleSet::TAKUZU.with_balance(Balance::Free);
+    let wide = Grid::with_rules(1, 101, rules).unwrap().pretty().indices(true).to_string();
+    let lines: Vec<_> = wide.lines().collect();
+    assert!(lines[0].ends_with("  98  99 100"));
+    assert_eq!(lines[0].chars().count(), lines[2].chars().count() - 2);
+    let wider = Grid::with_rules(1, 1002, rules).unwrap().pretty().indices(true).to_string();
+    let lines: Vec<_> = wider.lines().collect();
+    assert!(lines[0].ends_with(" 998 999   0   1"));
+    assert_eq!(lines[0].chars().count(), lines[2].chars().count() - 2);
+}
+
+#[test]
+fn test_size_errors() {
+    use takuzu::{
+        GridParseError::{BadSize, UnexpectedCharacter},
+        GridSizeError::*,
+    };
+
+    assert_eq!("".parse::<Grid>(), Err(BadSize(EmptyGrid)));
+    assert_eq!("..\n..\n..\n".parse::<Grid>(), Err(BadSize(OddNumberOfRows(3))));
+    assert_eq!("...\n...\n".parse::<Grid>(), Err(BadSize(OddNumberOfColumns(3))));
+    assert_eq!(
+        "..\n....\n".parse::<Grid>(),
+        Err(BadSize(NotARectangle { line: 2, found: 4, expected: 2 }))
+    );
+    assert_eq!("0?.\n..\n".parse::<Grid>(), Err(UnexpectedCharacter('?')));
+    assert_eq!("0=1 ?\n".parse::<Grid>(), Err(UnexpectedCharacter('?')));
+    assert_eq!(Grid::with_dimensions(4, 6).unwrap().dimensions(), (4, 6));
+}
+
+#[test]
+fn test_relaxed_balance() {
+    use takuzu::{GridParseError::BadSize, GridSizeError::OddNumberOfRows};
+
+    let rules = RuleSet::TAKUZU.with_balance(Balance::Relaxed);
+    assert_eq!("...\n...\n...\n".parse::<Grid>(), Err(BadSize(OddNumberOfRows(3))));
+    let grid = Grid::parse_with_rules("...\n...\n...\n", rules).unwrap();
+    assert_eq!(grid.solve().unwrap().len(), 84);
+    let grid = Grid::with_rules(3, 4, rules).unwrap();
+    assert_eq!(grid.solve().unwrap().len(), 72);
+    let grid = Grid::parse_with_rules("0.0\n...\n...\n", rules).unwrap();
+    assert!(grid.solve().unwrap().iter().all(|solution| solution.as_slice()[1].is_filled()));
+    let grid = Grid::parse_with_rules("00.\n...\n...\n", rules).unwrap();
+    assert!(grid.solve().unwrap().iter().all(|solution| solution.to_string().starts_with("001")));
+}
+
+#[test]
+fn test_rule_sets() {
+    let count = |rules: RuleSet, rows, cols| {
+        Grid::with_rules(rows, cols, rules).unwrap().solve().unwrap().len()
+    };
+
+    assert_eq!(count(RuleSet::TAKUZU, 4, 4), 72);
+    assert_eq!(count(RuleSet::TAKUZU.with_unique_lines(false), 4, 4), 90);
+    assert_eq!(count(RuleSet::TAKUZU.with_max_run(Some(3)), 4, 4), 72);
+    assert_eq!(count(RuleSet::TAKUZU.with_max_run(None), 4, 4), 72);
+    let checkerboard = RuleSet::TAKUZU.with_max_run(Some(1)).with_balance(Balance::Free);
+    assert_eq!(count(checkerboard.with_unique_lines(false), 3, 5), 2);
+    let grid = Grid::parse_with_rules("11.\n...\n...\n", checkerboard).unwrap();
+    assert!(!grid.is_legal());
+
+    let solve = |s: &str, rules: RuleSet| {
+        let grid = Grid::parse_with_rules(s, rules).unwrap();
+        let (solutions, stats) = grid.solve_with_stats().unwrap();
+        let solutions = solutions.iter().map(ToString::to_string).collect::<Vec<_>>();
+        (solutions, stats.filled(Rule::Rule1))
+    };
+    let runs = ".0.1..\n..1.1.\n1..1..\n..11..\n.....0\n....00\n";
+    let (solutions, filled) = solve(runs, RuleSet::TAKUZU.with_max_run(Some(3)));
+    assert_eq!(solutions, ["000111\n001011\n100101\n011100\n110010\n111000\n"]);
+    assert!(filled > 0);
+    assert_eq!(solve(runs, RuleSet::TAKUZU).0, Vec::<String>::new());
+    let unbalanced = "0.0..\n0..1.\n...00\n0....\n.0.00\n";
+    let (solutions, _) = solve(unbalanced, RuleSet::TAKUZU.with_balance(Balance::Free));
+    assert_eq!(solutions, ["01001\n01010\n10100\n01011\n00100\n"]);
+    let relaxed = RuleSet::TAKUZU.with_balance(Balance::Relaxed);
+    assert_eq!(solve(unbalanced, relaxed).0, Vec::<String>::new());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "invalid standard color 16")]
+fn test_invalid_standard_color() {
+    let _ = takuzu::Color::Standard(16).to_string();
+}
+
+#[test]
+#[should_panic(expected = "the maximum run length must be at least 1")]
+fn test_empty_max_run() {
+    let _ = RuleSet::TAKUZU.with_max_run(Some(0));
+}
+
+#[test]
+#[should_panic(expected = "cell coordinates out of bounds")]
+fn test_play_out_of_bounds() {
+    let puzzle = fs::read_to_string(GRIDS_DIR.join("grid5")).unwrap().parse::<Grid>().unwrap();
+    let session = PlaySession::new(puzzle.clone()).unwrap();
+    let _ = session.is_given((0, puzzle.cols()));
+}
+
+#[test]
+fn test_edges() {
+    use takuzu::{
+        Edge,
+        GridParseError::{MisplacedCharacter, UnexpectedCharacter},
+    };
+
+    let input = "0=. .\n    x\n. . .\n";
+    let rules = RuleSet::TANGO.with_balance(Balance::Relaxed);
+    let grid = Grid::parse_with_rules(input, rules).unwrap();
+    assert_eq!(grid.to_string(), input);
+    assert_eq!(grid.edge_right((0, 0)), Some(Edge::Equal));
+    assert_eq!(grid.edge_below((0, 2)), Some(Edge::Opposite));
+    assert_eq!(grid.edge_below((0, 0)), None);
+    assert!(grid.solve().unwrap().iter().all(|solution| {
+        solution[(0, 1)] == solution[(0, 0)] && solution[(1, 2)] != solution[(0, 2)]
+    }));
+    let mut grid = grid;
+    grid.set_edge_right((0, 0), None);
+    grid.set_edge_below((0, 2), None);
+    assert!(!grid.has_edges());
+    assert_eq!(grid.to_string(), "0..\n...\n");
+    assert_eq!(
+        "0 1\n= \n".parse::<Grid>(),
+        Err(MisplacedCharacter { line: 2, column: 1, found: '=' })
+    );
+    assert_eq!(
+        "0=1\n\n1 =\n".parse::<Grid>(),
+        Err(MisplacedCharacter { line: 3, column: 3, found: '=' })
+    );
+    assert_eq!("01 \n10\n".parse::<Grid>(), Err(UnexpectedCharacter(' ')));
+    assert_eq!("0 1\n1 0\n".parse::<Grid>(), Err(UnexpectedCharacter(' ')));
+    assert_eq!("0=1\n\n1 0\n".parse::<Grid>().unwrap().rules(), RuleSet::TANGO);
+    assert_eq!("0=1  \n\n1 0\n".parse::<Grid>().unwrap().dimensions(), (2, 2));
+    assert_eq!("01\n10\n".parse::<Grid>().unwrap().rules(), RuleSet::TAKUZU);
+}
+
+#[test]
+fn test_edge_grid_default_rules() {
+    let input = fs::read_to_string(GRIDS_DIR.join("grid6")).unwrap();
+    let output = fs::read_to_string(GRIDS_DIR.join("output6")).unwrap();
+    let grid = input.parse::<Grid>().unwrap();
+    assert_eq!(grid.solve().unwrap(), [output.parse::<Grid>().unwrap()]);
+}
+
+#[test]
+fn test_play_session() {
+    let puzzle = fs::read_to_string(GRIDS_DIR.join("grid5")).unwrap().parse::<Grid>().unwrap();
+    let solution = fs::read_to_string(GRIDS_DIR.join("output5")).unwrap().parse::<Grid>().unwrap();
+    assert_eq!(PlaySession::new(Grid::new(2).unwrap()).unwrap_err(), PlayError::MultipleSolutions);
+    assert_eq!(PlaySession::new(Grid::new(12).unwrap()).unwrap_err(), PlayError::MultipleSolutions);
+    let mut session = PlaySession::new(puzzle.clone()).unwrap();
+    let given = puzzle.as_slice().iter().position(|cell| cell.is_filled()).unwrap();
+    let given = (given / puzzle.cols(), given % puzzle.cols());
+    let empty = puzzle.next_empty().unwrap();
+    assert!(session.is_given(given));
+    assert!(!session.is_given(empty));
+    assert_eq!(session.enter(given, Cell::Empty), Err(PlayError::Given(given)));
+
+    session.enter(empty, !solution[empty]).unwrap();
+    session.enter(empty, solution[empty]).unwrap();
+    assert!(session.mistakes().is_empty());
+    session.mark(empty, Cell::Zero, true).unwrap();
+    session.mark(empty, Cell::Zero, true).unwrap();
+    assert_eq!(session.marks(empty), Marks { zero: true, one: false });
+    session.set_note(given, Some("start here".to_owned()));
+    assert_eq!(session.note(given), Some("start here"));
+    assert_eq!(session.history().len(), 4);
+    assert_eq!(
+        *session.history()[1],
+        Move::Enter { coord: empty, old: !solution[empty], new: solution[empty] }
+    );
+    assert!(!session.is_solved());
+    let wrong = (0..puzzle.rows())
+        .flat_map(|i| (0..puzzle.cols()).map(move |j| (i, j)))
+        .rfind(|&coord| coord != empty && !session.is_given(coord))
+        .unwrap();
+    session.enter(wrong, !solution[wrong]).unwrap();
+    assert_eq!(session.mistakes(), [wrong]);
+    for i in 0..puzzle.rows() {
+        for j in 0..puzzle.cols() {
+            if !session.is_given((i, j)) {
+                session.enter((i, j), solution[(i, j)]).unwrap();
+            }
+        }
+    }
+    assert!(session.is_solved());
+    assert_eq!(session.puzzle(), &puzzle);
+}
+
+#[test]
+fn test_move_log() {
+    let enter = |coord, new| Move::Enter { coord, old: Cell::Empty, new };
+    let mut log = MoveLog::new();
+    assert!(log.undo().is_none() && log.redo().is_none());
+    log.push(enter((0, 0), Cell::Zero));
+    log.push(enter((0, 1), Cell::Zero));
+    assert_eq!(log.undo(), Some(&enter((0, 1), Cell::Zero)));
+    log.push(enter((0, 1), Cell::One));
+    log.push(enter((1, 1), Cell::One));
+    assert_eq!(log.len(), 4);
+    assert_eq!(log.path().len(), 3);
+    log.undo();
+    log.undo();
+    assert_eq!(log.branches().count(), 2);
+    assert_eq!(log.redo(), Some(&enter((0, 1), Cell::One)));
+    log.undo();
+    assert_eq!(log.redo_branch(0), Some(&enter((0, 1), Cell::Zero)));
+    assert!(!log.can_redo());
+    log.undo();
+    log.push(enter((0, 1), Cell::One));
+    assert_eq!(log.len(), 4);
+    assert_eq!(log.redo(), Some(&enter((1, 1), Cell::One)));
+}
+
+#[test]
+fn test_save_session() {
+    let puzzle = fs::read_to_string(GRIDS_DIR.join("grid6")).unwrap();
+    let puzzle = Grid::parse_with_rules(&puzzle, RuleSet::TANGO).unwrap();
+    let mut session = PlaySession::new(puzzle).unwrap();
+    session.enter((0, 0), Cell::One).unwrap();
+    session.enter((0, 1), Cell::Zero).unwrap();
+    session.undo();
+    session.mark((0, 1), Cell::One, true).unwrap();
+    session.set_note((2, 0), Some("a \"given\"\\\n\u{7}".to_owned()));
+    session.undo();
+    session.undo();
+    session.redo_branch(0);
+    assert_eq!(session.grid()[(0, 1)], Cell::Zero);
+    session.pause();
+    let save = session.save();
+    let loaded = PlaySession::load(&save).unwrap();
+    let without_elapsed = |save: &str| {
+        save.lines().filter(|line| !line.starts_with("elapsed")).collect::<Vec<_>>().join("\n")
+    };
+    assert_eq!(without_elapsed(&loaded.save()), without_elapsed(&save));
+    assert_eq!(loaded.grid(), session.grid());
+    assert_eq!(loaded.log(), session.log());
+    assert!(loaded.elapsed() >= session.elapsed());
+    let mut loaded = loaded;
+    assert!(loaded.undo() && loaded.redo_branch(1) && loaded.redo());
+    assert_eq!(loaded.marks((0, 1)), Marks { zero: false, one: true });
+    assert_eq!(loaded.note((2, 0)), Some("a \"given\"\\\n\u{7}"));
+
+    let corrupt = save.replacen("0 enter 0 0 . 1", "0 enter 0 0 0 1", 1);
+    assert_eq!(PlaySession::load(&corrupt).unwrap_err(), PlayError::InvalidSave(6));
+    let given = save.replacen("0 enter 0 0 . 1", "0 enter 2 0 . 1", 1);
+    assert_eq!(PlaySession::load(&given).unwrap_err(), PlayError::InvalidSave(6));
+    assert_eq!(PlaySession::load("").unwrap_err(), PlayError::InvalidSave(1));
+    // The mark is on a branch other than the current one.
+    let branch = save.replacen("1 mark 0 1 -- -1", "1 mark 0 1 0- -1", 1);
+    assert_eq!(PlaySession::load(&branch).unwrap_err(), PlayError::InvalidSave(8));
+    let options = SolveOptions::new().with_max_nodes(1);
+    let stopped = PlaySession::load_with(&save, &options).unwrap_err();
+    assert_eq!(stopped, PlayError::Stopped(StopReason::MaxNodes));
+    let moves = save.lines().find(|line| line.starts_with("moves ")).unwrap();
+    for huge in [usize::MAX, usize::MAX - 4, 1000] {
+        let huge = save.replacen(moves, &format!("moves {huge}"), 1);
+        assert_eq!(PlaySession::load(&huge).unwrap_err(), PlayError::InvalidSave(4));
+    }
+}
+
+#[test]
+fn test_legality_tracker() {
+    let tango = fs::read_to_string(GRIDS_DIR.join("grid6")).unwrap();
+    let grids = [
+        Grid::with_dimensions(6, 4).unwrap(),
+        Grid::with_rules(3, 5, RuleSet::TAKUZU.with_balance(Balance::Relaxed)).unwrap(),
+        Grid::with_rules(4, 4, RuleSet::TAKUZU.with_max_run(Some(1))).unwrap(),
+        Grid::parse_with_rules(&tango, RuleSet::TANGO).unwrap(),
+    ];
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    let mut random = |n: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        usize::try_from(seed % n as u64).unwrap()
+    };
+    for grid in grids {
+        let mut tracker = LegalityTracker::new(grid.clone());
+        let mut grid = grid;
+        for _ in 0..2000 {
+            let coord = (random(grid.rows()), random(grid.cols()));
+            let cell = [Cell::Zero, Cell::One, Cell::Empty][random(3)];
+            grid[coord] = cell;
+            tracker.set(coord, cell);
+            assert_eq!(tracker.grid(), &grid);
+            assert_eq!(tracker.is_legal(), grid.is_legal(), "\n{grid}");
+            for i in 0..grid.rows() {
+                for j in 0..grid.cols() {
+                    assert_eq!(tracker.is_cell_legal((i, j)), grid.is_cell_legal((i, j)));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_solve_stats() {
+    let read_grid =
+        |name| fs::read_to_string(GRIDS_DIR.join(name)).unwrap().parse::<Grid>().unwrap();
+    let grid = read_grid("grid3");
+    let empty = grid.as_slice().iter().filter(|cell| cell.is_empty()).count();
+    let (solutions, stats) = grid.solve_with_stats().unwrap();
+    assert_eq!(solutions.len(), 1);
+    assert_eq!((stats.nodes, stats.branch_points, stats.dead_ends), (1, 0, 0));
+    assert_eq!(stats.filled_total(), empty);
+    assert_eq!(Rule::ALL.iter().map(|&rule| stats.filled(rule)).sum::<usize>(), empty);
+    assert_eq!(stats.filled(Rule::Edges), 0);
+
+    let (solutions, stats) = read_grid("grid4").solve_with_stats().unwrap();
+    assert_eq!(solutions, read_grid("grid4").solve().unwrap());
+    assert!(stats.branch_points >= solutions.len() - 1);
+    assert!(stats.nodes > stats.branch_points && stats.max_depth > 1);
+    assert!(stats.filled(Rule::Lookahead) > 0);
+}
+
+#[test]
+fn test_lookahead() {
+    let s = fs::read_to_string(GRIDS_DIR.join("grid1")).unwrap();
+    let mut grid = s.parse::<Grid>().unwrap();
+    let solutions = grid.solve().unwrap();
+    let rules = [Rule::Rule1, Rule::Edges, Rule::Rule2, Rule::Rule3];
+    while rules.iter().any(|&rule| grid.apply_rule(rule)) {}
+    let before = grid.clone();
+    assert!(grid.apply_rule(Rule::Lookahead));
+    let (before, after) = (before.as_slice(), grid.as_slice());
+    let filled: Vec<_> = (0..after.len()).filter(|&k| before[k] != after[k]).collect();
+    assert!(!filled.is_empty());
+    for &k in &filled {
+        assert!(before[k].is_empty());
+        assert!(solutions.iter().all(|solution| solution.as_slice()[k] == after[k]));
+    }
+}
+
+#[test]
+fn test_apply_rule() {
+    let s = fs::read_to_string(GRIDS_DIR.join("grid6")).unwrap();
+    let mut grid = Grid::parse_with_rules(&s, RuleSet::TANGO).unwrap();
+    let mut applied = Vec::new();
+    loop {
+        let mut expected = grid.clone();
+        let rule = Rule::ALL.iter().copied().find(|&rule| expected.apply_rule(rule));
+        assert_eq!(grid.apply_rules(), rule);
+        assert_eq!(grid, expected);
+        let Some(rule) = rule else { break };
+        applied.push(rule);
+    }
+    assert!(applied.contains(&Rule::Edges));
+}
+
+#[test]
+fn test_solve_limits() {
+    let grid = fs::read_to_string(GRIDS_DIR.join("grid4")).unwrap().parse::<Grid>().unwrap();
+    let all = grid.solve().unwrap();
+    assert!(all.len() > 3);
+
+    let outcome = grid.solve_with(&SolveOptions::new()).unwrap();
+    assert!(outcome.is_complete());
+    assert_eq!(outcome.solutions, all);
+
+    let outcome = grid.solve_with(&SolveOptions::new().with_max_solutions(3)).unwrap();
+    assert_eq!(outcome.stopped, Some(StopReason::MaxSolutions));
+    assert_eq!(outcome.solutions, all[..3]);
+    let outcome = grid.solve_with(&SolveOptions::new().with_max_solutions(all.len())).unwrap();
+    assert!(outcome.is_complete());
+    let outcome = grid.solve_with(&SolveOptions::new().with_max_solutions(0)).unwrap();
+    assert!(outcome.is_complete());
+    assert_eq!(outcome.solutions, all);
+
+    let outcome = grid.solve_with(&SolveOptions::new().with_max_nodes(10)).unwrap();
+    assert_eq!(outcome.stopped, Some(StopReason::MaxNodes));
+    assert_eq!(outcome.stats.nodes, 10);
+    assert!(outcome.solutions.iter().all(|solution| all.contains(solution)));
+
+    let options = SolveOptions::new().with_deadline(Instant::now());
+    assert_eq!(grid.solve_with(&options).unwrap().stopped, Some(StopReason::Timeout));
+    let options = SolveOptions::new().with_timeout(Duration::ZERO).with_max_nodes(10);
+    assert_eq!(grid.solve_with(&options).unwrap().stopped, Some(StopReason::Timeout));
+
+    let options = SolveOptions::new().with_cancel(Arc::new(AtomicBool::new(true)));
+    let outcome = grid.solve_with(&options).unwrap();
+    assert_eq!((outcome.stopped, outcome.stats.nodes), (Some(StopReason::Cancelled), 0));
+}
+
+#[test]
+fn test_solve_observer() {
+    /// Rebuilds the grids of the search from the events.
+    struct Mirror {
+        grid: Grid,
+        assigned: usize,
+        guesses: usize,
+        backtracks: usize,
+        solutions: Vec<Grid>,
+    }
+
+    impl SolveObserver for Mirror {
+        fn on_assign(&mut self, coord: (usize, usize), value: Cell, _: Rule) {
+            assert!(self.grid[coord].is_empty());
+            self.grid[coord] = value;
+            self.assigned += 1;
+        }
+        fn on_guess(&mut self, grid: &Grid, _: (usize, usize)) {
+            self.grid = grid.clone();
+            self.guesses += 1;
+        }
+        fn on_backtrack(&mut self, grid: &Grid) {
+            assert_eq!(*grid, self.grid);
+            assert!(!grid.is_legal());
+            self.backtracks += 1;
+        }
+        fn on_solution(&mut self, solution: &Grid) {
+            assert_eq!(*solution, self.grid);
+            self.solutions.push(solution.clone());
+        }
+    }
+
+    for (name, rules) in [("grid4", RuleSet::TAKUZU), ("grid6", RuleSet::TANGO)] {
+        let s = fs::read_to_string(GRIDS_DIR.join(name)).unwrap();
+        let grid = Grid::parse_with_rules(&s, rules).unwrap();
+        let mut mirror = Mirror {
+            grid: grid.clone(),
+            assigned: 0,
+            guesses: 0,
+            backtracks: 0,
+            solutions: vec![],
+        };
+        let outcome = grid.solve_observed(&SolveOptions::new(), &mut mirror).unwrap();
+        assert_eq!(mirror.solutions, outcome.solutions);
+        assert_eq!(mirror.solutions, grid.solve().unwrap());
+        assert_eq!(mirror.assigned, outcome.stats.filled_total());
+        assert_eq!(mirror.guesses, outcome.stats.nodes - 1);
+        assert_eq!(mirror.backtracks, outcome.stats.dead_ends);
+    }
+}
+
+#[test]
+fn test_verify() {
+    for (name, output, rules) in
+        [("grid1", "output1", RuleSet::TAKUZU), ("grid6", "output6", RuleSet::TANGO)]
+    {
+        let read = |name| fs::read_to_string(GRIDS_DIR.join(name)).unwrap();
+        let grid = Grid::parse_with_rules(&read(name), rules).unwrap();
+        let mut expected = grid.parse_solutions(&read(output)).unwrap();
+        assert_eq!(expected, grid.solve().unwrap());
+        expected.reverse();
+        assert!(grid.verify(&expected).unwrap().is_ok());
+    }
+
+    let grid = fs::read_to_string(GRIDS_DIR.join("grid1")).unwrap().parse::<Grid>().unwrap();
+    let solutions = grid.solve().unwrap();
+    let mut expected = solutions[1..].to_vec();
+    expected.push(solutions[2].clone());
+    let verification = Verification::new(&solutions, &expected);
+    assert!(!verification.is_ok());
+    assert_eq!(verification.missing, [solutions[2].clone()]);
+    assert_eq!(verification.extra, [solutions[0].clone()]);
+    assert_eq!(grid.parse_solutions(""), Ok(Vec::new()));
+    let output = fs::read_to_string(GRIDS_DIR.join("output1")).unwrap();
+    let spaced = format!("\n{}\n\n", output.replace("\n\n", "\n\n\n"));
+    assert_eq!(grid.parse_solutions(&spaced), Ok(solutions));
+    assert_eq!(grid.parse_solutions("\n\n"), Ok(Vec::new()));
+    assert!(grid.parse_solutions("0101\n").is_err());
+}
+
+#[test]
+fn test_lines() {
+    use takuzu::Edge;
+
+    let grid = "10.1\n0110\n1.0.\n.0..\n".parse::<Grid>().unwrap();
+    assert_eq!((grid.iter_rows().len(), grid.iter_cols().len()), (4, 4));
+    let (row, col) = (grid.row(1), grid.col(1));
+    assert_eq!(row.to_string(), "0110");
+    assert_eq!(col.to_string(), "01.0");
+    assert_eq!(format!("{:?}", grid.row(3)), "[Empty, Zero, Empty, Empty]");
+    assert_eq!(
+        (col.len(), col[1], col.get(2), col.get(4)),
+        (4, Cell::One, Some(Cell::Empty), None)
+    );
+    assert_eq!((col.zeros(), col.ones(), col.empties()), (2, 1, 1));
+    assert_eq!(col.count(Cell::Zero), 2);
+    assert!(row.is_filled() && !col.is_filled());
+    assert_eq!((row.longest_run(), row.run_at(0), row.run_at(2)), (2, 1, 2));
+    assert_eq!((grid.row(3).longest_run(), grid.row(3).run_at(0)), (1, 0));
+    assert!(grid.row(0).matches(grid.row(0)) && !grid.row(0).matches(row));
+    assert!(grid.row(3).matches(grid.col(0)) && !grid.col(0).matches(grid.row(3)));
+    assert_eq!(grid.row(1), [Cell::Zero, Cell::One, Cell::One, Cell::Zero][..]);
+    assert_ne!(grid.row(0), grid.row(2));
+    assert_eq!(grid.iter_cols().filter(Line::is_filled).count(), 0);
+    let cells: Vec<_> = grid.iter_rows().flatten().collect();
+    assert_eq!(cells, grid.as_slice());
+    let transpose: Vec<_> = grid.iter_cols().flatten().collect();
+    assert_eq!(transpose, grid.transpose().as_slice());
+    assert_eq!(grid.row(0).edge_after(0), None);
+
+    let rules = RuleSet::TANGO.with_balance(Balance::Relaxed);
+    let grid = Grid::parse_with_rules("0=1 .\n    x\n1 . .\n", rules).unwrap();
+    let edges =
+        |line: Line<'_>| (0..line.len() - 1).map(|k| line.edge_after(k)).collect::<Vec<_>>();
+    assert_eq!(edges(grid.row(0)), [Some(Edge::Equal), None]);
+    assert_eq!(edges(grid.row(1)), [None, None]);
+    assert_eq!(edges(grid.col(2)), [Some(Edge::Opposite)]);
+}
+
+#[test]
+fn test_symmetries() {
+    use takuzu::Edge;
+
+    let rules = RuleSet::TAKUZU.with_balance(Balance::Relaxed);
+    let grid = Grid::parse_with_rules("0=1 .\n    x\n1 . .\n", rules).unwrap();
+    let transpose = grid.transpose();
+    assert_eq!(transpose.dimensions(), (3, 2));
+    assert_eq!((transpose[(1, 0)], transpose[(0, 1)]), (Cell::One, Cell::One));
+    assert_eq!(transpose.edge_below((0, 0)), Some(Edge::Equal));
+    assert_eq!(transpose.edge_right((2, 0)), Some(Edge::Opposite));
+    assert_eq!(transpose.transpose(), grid);
+    assert_eq!(grid.rotate90(), transpose.flip_horizontal());
+    assert_eq!(grid.rotate270(), transpose.flip_vertical());
+    assert_eq!(grid.rotate180(), grid.flip_horizontal().flip_vertical());
+    assert_eq!(grid.rotate90().rotate90(), grid.rotate180());
+    assert_eq!(grid.rotate90().rotate270(), grid);
+    assert_eq!(grid.flip_horizontal().edge_right((0, 1)), Some(Edge::Equal));
+    assert_eq!(grid.invert()[(0, 0)], Cell::One);
+    assert_eq!(grid.invert().edge_right((0, 0)), Some(Edge::Equal));
+    assert_eq!(grid.invert().invert(), grid);
+
+    assert_eq!(grid.permute_cols(&[2, 1, 0]), Some(grid.flip_horizontal()));
+    assert_eq!(grid.permute_rows(&[1, 0]), Some(grid.flip_vertical()));
+    assert_eq!(grid.permute_cols(&[1, 2, 0]), None);
+    let free = Grid::parse_with_rules("01.\n1..\n", rules.with_max_run(None)).unwrap();
+    let permuted = free.permute_cols(&[1, 2, 0]).unwrap();
+    assert_eq!(permuted.to_string(), "1.0\n..1\n");
+    assert_eq!(permuted.solve().unwrap().len(), free.solve().unwrap().len());
+
+    let canonical = grid.canonical();
+    assert_eq!(canonical[(0, 0)], Cell::Zero);
+    assert_eq!(grid.rotate90().invert().canonical(), canonical);
+    assert_eq!(grid.flip_vertical().canonical(), canonical);
+    assert_ne!(free.canonical(), grid.canonical());
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_par_solve() {
+    let grids = [
+        ("grid1", RuleSet::TAKUZU),
+        ("grid2", RuleSet::TAKUZU),
+        ("grid3", RuleSet::TAKUZU),
+        ("grid4", RuleSet::TAKUZU),
+        ("grid5", RuleSet::TAKUZU),
+        ("grid6", RuleSet::TANGO),
+    ];
+    for (name, rules) in grids {
+        let s = fs::read_to_string(GRIDS_DIR.join(name)).unwrap();
+        let grid = Grid::parse_with_rules(&s, rules).unwrap();
+        assert_eq!(grid.par_solve().unwrap(), grid.solve().unwrap(), "{name}");
+    }
+    let illegal = "111.\n....\n....\n....\n".parse::<Grid>().unwrap();
+    assert_eq!(illegal.par_solve(), Err(takuzu::GridError::Illegal));
+}
@@ -0,0 +1,156 @@
+#![warn(rust_2018_idioms)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::cargo)]
+#![warn(clippy::nursery)]
+
+//! Property-based tests of the invariants of grids and of the solver.
+
+use proptest::prelude::*;
+use takuzu::{AnsiGridDiff, Balance, Cell, Edge, Grid, RuleSet, SolveOptions, Theme};
+
+/// Maximum number of solutions of the grids the solution sets are compared
+/// on, the others being skipped.
+const MAX_SOLUTIONS: usize = 64;
+
+fn rules() -> impl Strategy<Value = RuleSet> {
+    let max_run = prop_oneof![1 => Just(None), 4 => (1..=3_usize).prop_map(Some)];
+    let balance = prop_oneof![
+        2 => Just(Balance::Equal),
+        1 => Just(Balance::Relaxed),
+        1 => Just(Balance::Free),
+    ];
+    (max_run, balance, any::<bool>()).prop_map(|(max_run, balance, unique_lines)| {
+        RuleSet::TAKUZU.with_max_run(max_run).with_balance(balance).with_unique_lines(unique_lines)
+    })
+}
+
+fn cell() -> impl Strategy<Value = Cell> {
+    prop_oneof![2 => Just(Cell::Empty), 1 => Just(Cell::Zero), 1 => Just(Cell::One)]
+}
+
+fn edge() -> impl Strategy<Value = Option<Edge>> {
+    prop_oneof![8 => Just(None), 1 => Just(Some(Edge::Equal)), 1 => Just(Some(Edge::Opposite))]
+}
+
+/// Returns grids of 1 to 6 rows and columns with random rules, cells and,
+/// half the time, edge constraints. The grids may break the rules.
+fn grid() -> impl Strategy<Value = Grid> {
+    (rules(), 1..=6_usize, 1..=6_usize, any::<bool>()).prop_flat_map(
+        |(rules, rows, cols, edges)| {
+            // Lines of odd lengths need unequal numbers of `0`s and `1`s.
+            let rules = if rows % 2 == 1 || cols % 2 == 1 {
+                match rules.balance() {
+                    Balance::Equal => rules.with_balance(Balance::Relaxed),
+                    _ => rules,
+                }
+            } else {
+                rules
+            };
+            let edges = if edges { 2 * rows * cols } else { 0 };
+            let cells = prop::collection::vec(cell(), rows * cols);
+            let edges = prop::collection::vec(edge(), edges);
+            (cells, edges).prop_map(move |(cells, edges)| {
+                let mut grid = Grid::with_rules(rows, cols, rules).unwrap();
+                grid.as_mut_slice().copy_from_slice(&cells);
+                for (k, edge) in edges.chunks(2).enumerate() {
+                    let (i, j) = (k / cols, k % cols);
+                    if j + 1 < cols {
+                        grid.set_edge_right((i, j), edge[0]);
+                    }
+                    if i + 1 < rows {
+                        grid.set_edge_below((i, j), edge[1]);
+                    }
+                }
+                grid
+            })
+        },
+    )
+}
+
+/// Returns legal grids: the cells of `grid()` are given in order,
+/// skipping those breaking the rules.
+fn legal_grid() -> impl Strategy<Value = Grid> {
+    grid().prop_map(|mut grid| {
+        let cells = grid.as_slice().to_vec();
+        grid.as_mut_slice().fill(Cell::Empty);
+        let cols = grid.cols();
+        for (index, cell) in cells.into_iter().enumerate() {
+            let coord = (index / cols, index % cols);
+            grid[coord] = cell;
+            if !grid.is_cell_legal(coord) {
+                grid[coord] = Cell::Empty;
+            }
+        }
+        grid
+    })
+}
+
+/// Returns all the solutions of a grid, or `None` if there are more than
+/// `MAX_SOLUTIONS`.
+fn solutions(grid: &Grid) -> Option<Vec<Grid>> {
+    let outcome = grid.solve_with(&SolveOptions::new().with_max_solutions(MAX_SOLUTIONS + 1));
+    outcome.ok().map(|outcome| outcome.solutions).filter(|s| s.len() <= MAX_SOLUTIONS)
+}
+
+/// Returns the images of a grid by its symmetries.
+fn symmetries(grid: &Grid) -> Vec<Grid> {
+    vec![
+        grid.transpose(),
+        grid.rotate90(),
+        grid.rotate180(),
+        grid.rotate270(),
+        grid.flip_horizontal(),
+        grid.flip_vertical(),
+        grid.invert(),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn display_round_trips(grid in grid()) {
+        let s = grid.to_string();
+        prop_assert_eq!(Grid::parse_with_rules(&s, grid.rules()), Ok(grid.clone()));
+        if !grid.has_edges() && grid.rules() == RuleSet::TAKUZU {
+            prop_assert_eq!(s.parse::<Grid>(), Ok(grid));
+        }
+    }
+
+    #[test]
+    fn solutions_are_legal_and_keep_the_givens(grid in legal_grid()) {
+        let Some(solutions) = solutions(&grid) else { return Ok(()) };
+        let error = Theme::DEFAULT.error.to_string();
+        for solution in &solutions {
+            prop_assert!(solution.is_filled() && solution.is_legal(), "{}", solution);
+            let givens = grid.as_slice().iter().zip(solution.as_slice());
+            prop_assert!(givens.filter(|(given, _)| given.is_filled()).all(|(a, b)| a == b));
+            prop_assert!(!AnsiGridDiff(&grid, solution).to_string().contains(&error));
+        }
+    }
+
+    #[test]
+    fn rules_keep_the_solutions(grid in legal_grid()) {
+        let Some(solutions) = solutions(&grid) else { return Ok(()) };
+        prop_assume!(!solutions.is_empty());
+        let mut deduced = grid;
+        while deduced.apply_rules().is_some() {
+            prop_assert_eq!(deduced.solve(), Ok(solutions.clone()), "{}", deduced);
+        }
+    }
+
+    #[test]
+    fn symmetries_keep_the_canonical_form(grid in grid()) {
+        let canonical = grid.canonical();
+        prop_assert_eq!(&canonical.canonical(), &canonical);
+        for image in symmetries(&grid) {
+            prop_assert_eq!(&image.canonical(), &canonical, "{}", image);
+        }
+    }
+
+    #[test]
+    fn symmetries_keep_the_number_of_solutions(grid in legal_grid()) {
+        let Some(solutions) = solutions(&grid) else { return Ok(()) };
+        for image in symmetries(&grid) {
+            prop_assert_eq!(image.solve().map(|s| s.len()), Ok(solutions.len()), "{}", image);
+        }
+    }
+}